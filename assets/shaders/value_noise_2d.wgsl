@@ -0,0 +1,55 @@
+#define_import_path value_noise::value_noise_2d
+
+#ifdef ARRAY
+#import bevy_compute_noise::util::texture2d_array as texture
+#else
+#import bevy_compute_noise::util::texture2d as texture
+#endif
+#import bevy_compute_noise::dispatch::{dispatch_location, outside_dispatch, dispatch_layer}
+
+// Matches `ValueNoise` in `examples/custom_noise.rs`.
+struct ValueNoise {
+    seed: u32,
+    frequency: f32,
+}
+@group(0) @binding(1) var<uniform> value_noise: ValueNoise;
+
+@compute @workgroup_size(16, 16)
+fn main(@builtin(global_invocation_id) invocation_id: vec3<u32>) {
+    let texel = dispatch_location(invocation_id);
+    if outside_dispatch(texel) { return; }
+    let uv = vec2<f32>(texel.xy) / vec2<f32>(textureDimensions(texture));
+
+    let value = value_noise_2d(uv, value_noise);
+    // Array and cube textures are bound with every layer, each dispatch writes one of them.
+#ifdef ARRAY
+    textureStore(texture, texel.xy, dispatch_layer(), vec4<f32>(value, 0.0, 0.0, 1.0));
+#else
+    textureStore(texture, texel.xy, vec4<f32>(value, 0.0, 0.0, 1.0));
+#endif
+}
+
+fn hash(p: vec3<u32>) -> f32 {
+    var v = p * 1664525u + 1013904223u;
+    v.x += v.y * v.z; v.y += v.z * v.x; v.z += v.x * v.y;
+    v ^= v >> vec3<u32>(16u);
+    v.x += v.y * v.z; v.y += v.z * v.x; v.z += v.x * v.y;
+    return f32(v.x) / 4294967295.0;
+}
+
+fn corner(cell: vec2<i32>, seed: u32) -> f32 {
+    return hash(vec3<u32>(bitcast<vec2<u32>>(cell), seed));
+}
+
+// Called by `Fbm<ValueNoise>` on 2D textures, so the signature is `fn(vec2<f32>, Noise) -> f32`.
+fn value_noise_2d(uv: vec2<f32>, noise: ValueNoise) -> f32 {
+    let p = uv * noise.frequency;
+    let cell = vec2<i32>(floor(p));
+    let t = smoothstep(vec2<f32>(0.0), vec2<f32>(1.0), fract(p));
+
+    return mix(
+        mix(corner(cell, noise.seed), corner(cell + vec2<i32>(1, 0), noise.seed), t.x),
+        mix(corner(cell + vec2<i32>(0, 1), noise.seed), corner(cell + vec2<i32>(1, 1), noise.seed), t.x),
+        t.y,
+    );
+}
@@ -0,0 +1,52 @@
+#define_import_path value_noise::value_noise_3d
+
+#import bevy_compute_noise::util::texture3d as texture
+#import bevy_compute_noise::dispatch::{dispatch_location, outside_dispatch}
+
+// Matches `ValueNoise` in `examples/custom_noise.rs`.
+struct ValueNoise {
+    seed: u32,
+    frequency: f32,
+}
+@group(0) @binding(1) var<uniform> value_noise: ValueNoise;
+
+@compute @workgroup_size(8, 8, 4)
+fn main(@builtin(global_invocation_id) invocation_id: vec3<u32>) {
+    let texel = dispatch_location(invocation_id);
+    if outside_dispatch(texel) { return; }
+    let uv = vec3<f32>(texel) / vec3<f32>(textureDimensions(texture));
+
+    let value = value_noise_3d(uv, value_noise);
+    textureStore(texture, texel, vec4<f32>(value, 0.0, 0.0, 1.0));
+}
+
+fn hash(p: vec3<u32>) -> f32 {
+    var v = p * 1664525u + 1013904223u;
+    v.x += v.y * v.z; v.y += v.z * v.x; v.z += v.x * v.y;
+    v ^= v >> vec3<u32>(16u);
+    v.x += v.y * v.z; v.y += v.z * v.x; v.z += v.x * v.y;
+    return f32(v.x) / 4294967295.0;
+}
+
+fn corner(cell: vec3<i32>, seed: u32) -> f32 {
+    return hash(bitcast<vec3<u32>>(cell) + vec3<u32>(0u, 0u, seed * 1031u));
+}
+
+// Called by `Fbm<ValueNoise>` on 3D and cube textures, and by `Spherical<ValueNoise>`.
+fn value_noise_3d(uv: vec3<f32>, noise: ValueNoise) -> f32 {
+    let p = uv * noise.frequency;
+    let cell = vec3<i32>(floor(p));
+    let t = smoothstep(vec3<f32>(0.0), vec3<f32>(1.0), fract(p));
+
+    let bottom = mix(
+        mix(corner(cell, noise.seed), corner(cell + vec3<i32>(1, 0, 0), noise.seed), t.x),
+        mix(corner(cell + vec3<i32>(0, 1, 0), noise.seed), corner(cell + vec3<i32>(1, 1, 0), noise.seed), t.x),
+        t.y,
+    );
+    let top = mix(
+        mix(corner(cell + vec3<i32>(0, 0, 1), noise.seed), corner(cell + vec3<i32>(1, 0, 1), noise.seed), t.x),
+        mix(corner(cell + vec3<i32>(0, 1, 1), noise.seed), corner(cell + vec3<i32>(1, 1, 1), noise.seed), t.x),
+        t.y,
+    );
+    return mix(bottom, top, t.z);
+}
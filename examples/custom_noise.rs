@@ -0,0 +1,97 @@
+use bevy::{prelude::*, render::render_resource::{Buffer, ShaderRef}};
+use bevy_compute_noise::{
+    buffer_pool::ComputeNoiseBufferPool,
+    noise::{generators::ComputeNoiseGenerator, ComputeNoise, ComputeNoiseShader, NoiseOp},
+    prelude::*,
+    ComputeNoiseGeneratorPlugin,
+};
+use bytemuck::{Pod, Zeroable};
+
+// A generator written in WGSL, see `assets/shaders/value_noise_2d.wgsl` and `value_noise_3d.wgsl`.
+// Registering it also makes `Fbm<ValueNoise>` and `Spherical<ValueNoise>` available.
+
+fn main() {
+    App::new()
+        .add_plugins((
+            DefaultPlugins,
+            ComputeNoisePlugin,
+            ComputeNoiseGeneratorPlugin::<ValueNoise>::default(),
+        ))
+        .add_systems(Startup, setup)
+        .run();
+}
+
+#[derive(Clone, Copy, Reflect, Pod, Zeroable)]
+#[reflect(Default)]
+#[repr(C)]
+struct ValueNoise {
+    seed: u32,
+    frequency: f32,
+}
+
+impl Default for ValueNoise {
+    fn default() -> Self {
+        Self { seed: 0, frequency: 8.0 }
+    }
+}
+
+impl ComputeNoise for ValueNoise {
+    const NOISE_OP: NoiseOp = NoiseOp::Generator;
+
+    fn buffers(&self, pool: &mut ComputeNoiseBufferPool, _size: ComputeNoiseSize) -> Vec<Buffer> {
+        vec![
+            pool.uniform("value_noise_buffer", bytemuck::cast_slice(&[*self]))
+        ]
+    }
+}
+
+impl ComputeNoiseGenerator for ValueNoise {
+    // The shaders are loaded from the assets folder, so there's nothing to embed.
+    fn embed_shaders(_app: &mut App) {}
+
+    fn shader_2d() -> ShaderRef {
+        "shaders/value_noise_2d.wgsl".into()
+    }
+
+    fn shader_3d() -> ShaderRef {
+        "shaders/value_noise_3d.wgsl".into()
+    }
+}
+
+impl ComputeNoiseShader for ValueNoise {
+    fn function_name() -> &'static str {
+        "value_noise_2d"
+    }
+
+    fn import_path() -> &'static str {
+        "value_noise::value_noise_2d"
+    }
+
+    fn struct_name() -> Option<&'static str> {
+        Some("ValueNoise")
+    }
+
+    fn import_path_3d() -> &'static str {
+        "value_noise::value_noise_3d"
+    }
+
+    fn function_name_3d() -> &'static str {
+        "value_noise_3d"
+    }
+}
+
+fn setup(
+    mut commands: Commands,
+    mut images: ResMut<Assets<Image>>,
+    mut noise_queue: ResMut<ComputeNoiseQueue>,
+) {
+    let value = images.add(ComputeNoiseImage::create_image(ComputeNoiseSize::D2(256, 256)));
+    let fbm = images.add(ComputeNoiseImage::create_image(ComputeNoiseSize::D2(256, 256)));
+
+    noise_queue.queue(value.clone(), ValueNoise::default()).unwrap();
+    noise_queue.queue(fbm.clone(), Fbm::<ValueNoise> { octaves: 6, ..default() }).unwrap();
+
+    commands.spawn(Camera2d);
+    commands.spawn((Sprite::from_image(value), Transform::from_xyz(-136.0, 0.0, 0.0)));
+    commands.spawn((Sprite::from_image(fbm), Transform::from_xyz(136.0, 0.0, 0.0)));
+}
@@ -24,7 +24,7 @@ fn setup(
     noise_queue.queue(
         image.clone(),
         Perlin::default()
-    );
+    ).unwrap();
 
     // generate noise and invert
     noise_queue.queue(
@@ -33,5 +33,5 @@ fn setup(
             Worley::default(),
             Invert
         )
-    );
+    ).unwrap();
 }
\ No newline at end of file
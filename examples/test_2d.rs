@@ -58,11 +58,11 @@ fn setup(
     // noise_queue.queue(handle.clone(), (
     //     Fbm::<Perlin> {
     //         noise: Perlin {
-    //             flags: PerlinFlags::TILEABLE.bits(),
+    //             flags: PerlinFlags::TILEABLE,
     //             ..default()
     //         },
     //         persistence: exp2(-0.85),
-    //         flags: FbmFlags::BILLOWY.bits(),
+    //         flags: FbmFlags::BILLOWY,
     //         ..default()
     //     },
     //     //Write(handle.clone(), Channel::R)
@@ -72,7 +72,7 @@ fn setup(
         Perlin::default(),
         Invert,
         Store(Channel::R)
-    ));
+    )).unwrap();
 
     commands.spawn((
         Mesh2d(meshes.add(quad)),
@@ -103,8 +103,8 @@ fn setup(
 //                 noise: Worley {
 //                     seed: *local,
 //                     frequency: 5.0,
-//                     flags: WorleyFlags::TILEABLE.bits(),
-//                     // flags: (PerlinFlags::default() | PerlinFlags::TILEABLE).bits()
+//                     flags: WorleyFlags::TILEABLE,
+//                     // flags: PerlinFlags::default() | PerlinFlags::TILEABLE
 //                 },
 //                 octaves: 4,
 //                 lacunarity: 2.0,
@@ -35,15 +35,15 @@ fn setup(
             noise: Perlin {
                 seed: 1,
                 frequency: 5.0,
-                // flags: (WorleyFlags::INVERT | WorleyFlags::TILEABLE).bits()
-                flags: (PerlinFlags::default() | PerlinFlags::TILEABLE).bits()
+                // flags: WorleyFlags::INVERT | WorleyFlags::TILEABLE
+                flags: PerlinFlags::default() | PerlinFlags::TILEABLE
             },
             octaves: 4,
             lacunarity: 2.0,
             persistence: 0.5,
             ..default()
         },
-    );
+    ).unwrap();
 
     let mut quad = Rectangle::default().mesh().build();
     if let Some(uvs) = quad.attribute_mut(Mesh::ATTRIBUTE_UV_0) {
@@ -81,8 +81,8 @@ fn setup(
 //                     noise: Perlin {
 //                         seed: *local,
 //                         frequency: 5.0,
-//                         // flags: (WorleyFlags::INVERT | WorleyFlags::TILEABLE).bits(),
-//                         flags: (PerlinFlags::default() | PerlinFlags::TILEABLE).bits()
+//                         // flags: WorleyFlags::INVERT | WorleyFlags::TILEABLE,
+//                         flags: PerlinFlags::default() | PerlinFlags::TILEABLE
 //                     },
 //                     octaves: 4,
 //                     lacunarity: 2.0,
@@ -0,0 +1,339 @@
+//! Bakes noise textures from a recipe file without opening a window.
+//!
+//! ```text
+//! bevy_compute_noise <recipe.ron> [--out <path>] [--seed <seed>] [--seeds <start>..<end>]
+//! ```
+//!
+//! Noise is generated on the GPU by a headless app, so a GPU adapter is still needed.
+
+use std::{ops::Range, path::{Path, PathBuf}, process::ExitCode, time::Duration};
+
+use bevy::{app::ScheduleRunnerPlugin, prelude::*, window::ExitCondition};
+use bevy_compute_noise::{
+    export::{ComputeNoiseSaveQueue, NoiseFileFormat},
+    noise::generators::{FbmFlags, ComputeNoiseGenerator},
+    noise_queue::QueueNoiseOp,
+    prelude::*,
+};
+use bitflags::Flags;
+use serde::Deserialize;
+
+const USAGE: &str = "usage: bevy_compute_noise <recipe.ron> [--out <path>] [--seed <seed>] [--seeds <start>..<end>]";
+
+#[derive(Deserialize)]
+struct Recipe {
+    size: RecipeSize,
+    #[serde(default = "default_format")]
+    format: RecipeFormat,
+    #[serde(default)]
+    mipmaps: bool,
+    /// Where the image is written, `--out` takes precedence.
+    output: Option<PathBuf>,
+    stack: Vec<RecipeOp>,
+}
+
+fn default_format() -> RecipeFormat {
+    RecipeFormat::Png
+}
+
+#[derive(Deserialize, Clone, Copy)]
+enum RecipeSize {
+    D2(u32, u32),
+    D3(u32, u32, u32),
+    D2Array(u32, u32, u32),
+    Cube(u32),
+}
+
+impl From<RecipeSize> for ComputeNoiseSize {
+    fn from(size: RecipeSize) -> Self {
+        match size {
+            RecipeSize::D2(width, height) => ComputeNoiseSize::D2(width, height),
+            RecipeSize::D3(width, height, depth) => ComputeNoiseSize::D3(width, height, depth),
+            RecipeSize::D2Array(width, height, layers) => ComputeNoiseSize::D2Array(width, height, layers),
+            RecipeSize::Cube(size) => ComputeNoiseSize::Cube(size),
+        }
+    }
+}
+
+#[derive(Deserialize, Clone, Copy)]
+enum RecipeFormat {
+    Png,
+    Png16,
+    Exr,
+    Ktx2,
+    PngAtlas(u32),
+}
+
+impl RecipeFormat {
+    fn extension(&self) -> &'static str {
+        match self {
+            Self::Png | Self::Png16 | Self::PngAtlas(_) => "png",
+            Self::Exr => "exr",
+            Self::Ktx2 => "ktx2",
+        }
+    }
+}
+
+impl From<RecipeFormat> for NoiseFileFormat {
+    fn from(format: RecipeFormat) -> Self {
+        match format {
+            RecipeFormat::Png => NoiseFileFormat::Png,
+            RecipeFormat::Png16 => NoiseFileFormat::Png16,
+            RecipeFormat::Exr => NoiseFileFormat::Exr,
+            RecipeFormat::Ktx2 => NoiseFileFormat::Ktx2,
+            RecipeFormat::PngAtlas(columns) => NoiseFileFormat::PngAtlas { columns },
+        }
+    }
+}
+
+/// Generator settings, flags are given by name, e.g. `flags: ["TILEABLE", "REMAP"]`.
+#[derive(Deserialize, Clone)]
+struct RecipeGenerator {
+    #[serde(default)]
+    seed: u32,
+    #[serde(default = "default_frequency")]
+    frequency: f32,
+    flags: Option<Vec<String>>,
+}
+
+fn default_frequency() -> f32 {
+    5.0
+}
+
+#[derive(Deserialize, Clone)]
+struct RecipeFbm {
+    #[serde(default = "default_octaves")]
+    octaves: u32,
+    #[serde(default = "default_lacunarity")]
+    lacunarity: f32,
+    #[serde(default = "default_persistence")]
+    persistence: f32,
+    #[serde(default)]
+    flags: Vec<String>,
+}
+
+fn default_octaves() -> u32 { 4 }
+fn default_lacunarity() -> f32 { 2.0 }
+fn default_persistence() -> f32 { 0.5 }
+
+#[derive(Deserialize, Clone)]
+enum RecipeOp {
+    Perlin(RecipeGenerator),
+    Worley(RecipeGenerator),
+    FbmPerlin(RecipeGenerator, RecipeFbm),
+    FbmWorley(RecipeGenerator, RecipeFbm),
+    Invert,
+    Threshold(f32),
+    SmoothStep(f32, f32),
+}
+
+fn parse_flags<F: Flags>(names: &[String]) -> Result<F, String> {
+    names.iter().try_fold(F::empty(), |flags, name| match F::from_name(name) {
+        Some(flag) => Ok(flags.union(flag)),
+        None => Err(format!("unknown flag `{name}`")),
+    })
+}
+
+impl RecipeGenerator {
+    fn perlin(&self, seed: Option<u32>) -> Result<Perlin, String> {
+        Ok(Perlin {
+            seed: seed.unwrap_or(self.seed),
+            frequency: self.frequency,
+            flags: match &self.flags {
+                Some(flags) => parse_flags::<PerlinFlags>(flags)?,
+                None => Perlin::default().flags,
+            },
+        })
+    }
+
+    fn worley(&self, seed: Option<u32>) -> Result<Worley, String> {
+        Ok(Worley {
+            seed: seed.unwrap_or(self.seed),
+            frequency: self.frequency,
+            flags: match &self.flags {
+                Some(flags) => parse_flags::<WorleyFlags>(flags)?,
+                None => Worley::default().flags,
+            },
+        })
+    }
+}
+
+impl RecipeFbm {
+    fn fbm<T: ComputeNoiseGenerator>(&self, noise: T) -> Result<Fbm<T>, String> {
+        Ok(Fbm {
+            noise,
+            octaves: self.octaves,
+            lacunarity: self.lacunarity,
+            persistence: self.persistence,
+            flags: parse_flags::<FbmFlags>(&self.flags)?,
+        })
+    }
+}
+
+impl RecipeOp {
+    /// `seed` replaces the seed of every generator in the stack.
+    fn queue_op(&self, seed: Option<u32>) -> Result<QueueNoiseOp, String> {
+        Ok(match self {
+            Self::Perlin(generator) => generator.perlin(seed)?.into(),
+            Self::Worley(generator) => generator.worley(seed)?.into(),
+            Self::FbmPerlin(generator, fbm) => fbm.fbm(generator.perlin(seed)?)?.into(),
+            Self::FbmWorley(generator, fbm) => fbm.fbm(generator.worley(seed)?)?.into(),
+            Self::Invert => Invert.into(),
+            Self::Threshold(value) => Threshold { value: *value, ..default() }.into(),
+            Self::SmoothStep(edge0, edge1) => SmoothStep { edge0: *edge0, edge1: *edge1 }.into(),
+        })
+    }
+}
+
+struct Args {
+    recipe: PathBuf,
+    out: Option<PathBuf>,
+    seed: Option<u32>,
+    seeds: Option<Range<u32>>,
+}
+
+fn parse_args() -> Result<Args, String> {
+    let mut args = std::env::args().skip(1);
+    let (mut recipe, mut out, mut seed, mut seeds) = (None, None, None, None);
+
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or_else(|| format!("missing value for `{arg}`"));
+        match arg.as_str() {
+            "--out" => out = Some(PathBuf::from(value()?)),
+            "--seed" => seed = Some(value()?.parse().map_err(|_| "`--seed` must be a number")?),
+            "--seeds" => {
+                let range = value()?;
+                let (start, end) = range.split_once("..").ok_or("`--seeds` must be a range, e.g. `0..16`")?;
+                seeds = Some(
+                    start.parse().map_err(|_| "invalid `--seeds` start")?
+                    ..end.parse().map_err(|_| "invalid `--seeds` end")?
+                );
+            }
+            "-h" | "--help" => return Err(USAGE.to_string()),
+            _ if recipe.is_none() && !arg.starts_with('-') => recipe = Some(PathBuf::from(arg)),
+            _ => return Err(format!("unexpected argument `{arg}`\n{USAGE}")),
+        }
+    }
+
+    Ok(Args { recipe: recipe.ok_or(USAGE)?, out, seed, seeds })
+}
+
+/// `noise.png` becomes `noise_7.png` for seed 7.
+fn seeded_path(path: &Path, seed: u32) -> PathBuf {
+    let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+    match path.extension() {
+        Some(extension) => path.with_file_name(format!("{stem}_{seed}.{}", extension.to_string_lossy())),
+        None => path.with_file_name(format!("{stem}_{seed}")),
+    }
+}
+
+/// A single image to generate and save.
+struct Bake {
+    path: PathBuf,
+    ops: Vec<QueueNoiseOp>,
+}
+
+#[derive(Resource)]
+struct Bakes {
+    size: ComputeNoiseSize,
+    mipmaps: bool,
+    format: NoiseFileFormat,
+    bakes: Vec<Bake>,
+}
+
+fn plan(args: &Args, recipe: &Recipe) -> Result<Bakes, String> {
+    let path = args.out.clone()
+        .or_else(|| recipe.output.clone())
+        .unwrap_or_else(|| args.recipe.with_extension(recipe.format.extension()));
+
+    let seeds: Vec<(PathBuf, Option<u32>)> = match args.seeds.clone() {
+        Some(seeds) => seeds.map(|seed| (seeded_path(&path, seed), Some(seed))).collect(),
+        None => vec![(path, args.seed)],
+    };
+
+    let bakes = seeds.into_iter()
+        .map(|(path, seed)| Ok(Bake {
+            path,
+            ops: recipe.stack.iter().map(|op| op.queue_op(seed)).collect::<Result<_, String>>()?,
+        }))
+        .collect::<Result<_, String>>()?;
+
+    Ok(Bakes {
+        size: recipe.size.into(),
+        mipmaps: recipe.mipmaps,
+        format: recipe.format.into(),
+        bakes,
+    })
+}
+
+fn queue_bakes(
+    mut bakes: ResMut<Bakes>,
+    mut images: ResMut<Assets<Image>>,
+    mut noise_queue: ResMut<ComputeNoiseQueue>,
+    mut save_queue: ResMut<ComputeNoiseSaveQueue>,
+    mut exit: EventWriter<AppExit>,
+) {
+    let (size, mipmaps, format) = (bakes.size, bakes.mipmaps, bakes.format);
+    for bake in bakes.bakes.drain(..) {
+        let image = images.add(match mipmaps {
+            true => ComputeNoiseImage::create_mipmapped_image(size, true),
+            false => ComputeNoiseImage::create_image(size),
+        });
+
+        // One sequence, so the ops run in order on the image.
+        if let Err(error) = noise_queue.queue(image.clone(), bake.ops) {
+            error!("{error}");
+            exit.send(AppExit::error());
+            return;
+        }
+        save_queue.save_noise_image(image, bake.path, format);
+    }
+}
+
+fn exit_when_saved(save_queue: Res<ComputeNoiseSaveQueue>, mut exit: EventWriter<AppExit>) {
+    if save_queue.pending() == 0 {
+        exit.send(match save_queue.failed() {
+            0 => AppExit::Success,
+            _ => AppExit::error(),
+        });
+    }
+}
+
+fn main() -> ExitCode {
+    let result = parse_args().and_then(|args| {
+        let source = std::fs::read_to_string(&args.recipe)
+            .map_err(|error| format!("failed to read {}: {error}", args.recipe.display()))?;
+        let recipe: Recipe = ron::Options::default()
+            .with_default_extension(ron::extensions::Extensions::IMPLICIT_SOME)
+            .from_str(&source)
+            .map_err(|error| format!("failed to parse {}: {error}", args.recipe.display()))?;
+        plan(&args, &recipe)
+    });
+
+    let bakes = match result {
+        Ok(bakes) => bakes,
+        Err(error) => {
+            eprintln!("{error}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    match App::new()
+        .add_plugins((
+            DefaultPlugins.set(WindowPlugin {
+                primary_window: None,
+                exit_condition: ExitCondition::DontExit,
+                ..default()
+            }),
+            ScheduleRunnerPlugin::run_loop(Duration::ZERO),
+            ComputeNoisePlugin,
+        ))
+        .insert_resource(bakes)
+        .add_systems(Startup, queue_bakes)
+        .add_systems(Update, exit_when_saved)
+        .run()
+    {
+        AppExit::Success => ExitCode::SUCCESS,
+        AppExit::Error(_) => ExitCode::FAILURE,
+    }
+}
@@ -0,0 +1,99 @@
+use std::sync::Arc;
+
+use bevy::{
+    prelude::*,
+    render::{
+        render_resource::{Buffer, BufferInitDescriptor, BufferUsages},
+        renderer::{RenderDevice, RenderQueue},
+    },
+    utils::HashMap,
+};
+
+/// Pooled buffers and cached bind groups are dropped after going unused for this many frames.
+pub(crate) const MAX_UNUSED_FRAMES: u32 = 60;
+
+struct PooledBuffer {
+    buffer: Buffer,
+    /// Cloned into every sequence the buffer is handed to, the buffer is free again once only the pool holds it.
+    lease: Arc<()>,
+    unused_frames: u32,
+}
+
+/// Keeps the buffers handed to [`ComputeNoise::buffers`](crate::noise::ComputeNoise::buffers) around so
+/// noise queued every frame updates the same buffers with `write_buffer` instead of allocating new ones.
+///
+/// A buffer is only reused after the sequence it was given to has been dispatched, so queuing the same
+/// image several times in a frame, or sequences waiting on pipelines, never see each other's data.
+#[derive(Resource)]
+pub struct ComputeNoiseBufferPool {
+    render_device: RenderDevice,
+    render_queue: RenderQueue,
+    buffers: HashMap<(u64, BufferUsages), Vec<PooledBuffer>>,
+    leases: Vec<Arc<()>>,
+}
+
+impl FromWorld for ComputeNoiseBufferPool {
+    fn from_world(world: &mut World) -> Self {
+        Self {
+            render_device: world.resource::<RenderDevice>().clone(),
+            render_queue: world.resource::<RenderQueue>().clone(),
+            buffers: HashMap::default(),
+            leases: Vec::new(),
+        }
+    }
+}
+
+impl ComputeNoiseBufferPool {
+    pub fn render_device(&self) -> &RenderDevice {
+        &self.render_device
+    }
+
+    /// Returns a uniform buffer holding `contents`.
+    pub fn uniform(&mut self, label: &'static str, contents: &[u8]) -> Buffer {
+        self.get(label, contents, BufferUsages::UNIFORM | BufferUsages::COPY_DST)
+    }
+
+    /// Returns a read only storage buffer holding `contents`.
+    pub fn storage(&mut self, label: &'static str, contents: &[u8]) -> Buffer {
+        self.get(label, contents, BufferUsages::STORAGE | BufferUsages::COPY_DST)
+    }
+
+    fn get(&mut self, label: &'static str, contents: &[u8], usage: BufferUsages) -> Buffer {
+        let entries = self.buffers.entry((contents.len() as u64, usage)).or_default();
+
+        if let Some(entry) = entries.iter_mut().find(|entry| Arc::strong_count(&entry.lease) == 1) {
+            self.render_queue.write_buffer(&entry.buffer, 0, contents);
+            entry.unused_frames = 0;
+            self.leases.push(entry.lease.clone());
+            return entry.buffer.clone();
+        }
+
+        let buffer = self.render_device.create_buffer_with_data(&BufferInitDescriptor {
+            label: Some(label),
+            contents,
+            usage,
+        });
+        let lease = Arc::new(());
+        self.leases.push(lease.clone());
+        entries.push(PooledBuffer { buffer: buffer.clone(), lease, unused_frames: 0 });
+
+        buffer
+    }
+
+    /// Leases for every buffer handed out since the last call, these need to live until the buffers have been used.
+    pub(crate) fn take_leases(&mut self) -> Vec<Arc<()>> {
+        std::mem::take(&mut self.leases)
+    }
+
+    /// Ages free buffers and drops the ones that haven't been used in a while.
+    pub(crate) fn tick(&mut self) {
+        for entries in self.buffers.values_mut() {
+            entries.retain_mut(|entry| {
+                if Arc::strong_count(&entry.lease) > 1 { return true; }
+                entry.unused_frames += 1;
+                entry.unused_frames <= MAX_UNUSED_FRAMES
+            });
+        }
+        self.buffers.retain(|_, entries| !entries.is_empty());
+    }
+}
@@ -0,0 +1,44 @@
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ComputeNoiseError {
+    /// The plugin for this noise type hasn't been added to the app.
+    UnregisteredType(&'static str),
+    /// The noise type doesn't take an input image.
+    NoInputImage(&'static str),
+    /// The noise type doesn't take two input images.
+    NoInputImages(&'static str),
+    /// The noise type needs two input images, use `input_images`.
+    NeedsInputImages(&'static str),
+    /// Not all images in the sequence have the same dimension and number of layers.
+    DimensionMismatch,
+    /// The noise type doesn't provide a shader.
+    MissingShader(&'static str),
+    /// The generator's `ComputeNoiseShader::struct_name` is `None`, so wrappers like `Fbm` can't import it.
+    MissingNoiseStruct(&'static str),
+    /// The file format can't hold this kind of texture.
+    UnsupportedExport,
+    /// The noise type runs several passes over the whole texture and can't be queued with a region.
+    UnsupportedRegion(&'static str),
+    /// The noise type only supports 2D textures and was used on a 3D one.
+    Unsupported3d(&'static str),
+}
+
+impl fmt::Display for ComputeNoiseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnregisteredType(name) => write!(f, "{name} has not been registered, add its plugin to the app"),
+            Self::NoInputImage(name) => write!(f, "{name} doesn't support input images"),
+            Self::NoInputImages(name) => write!(f, "{name} doesn't support multiple input images"),
+            Self::NeedsInputImages(name) => write!(f, "{name} needs two input images, use input_images"),
+            Self::DimensionMismatch => write!(f, "not all images have the same dimension and number of layers"),
+            Self::MissingShader(name) => write!(f, "{name} doesn't provide a shader"),
+            Self::MissingNoiseStruct(name) => write!(f, "{name} has no struct_name, so Fbm, Spherical and cubes can't wrap it"),
+            Self::UnsupportedExport => write!(f, "only Ktx2 and PngAtlas can save 3D, array and cube textures"),
+            Self::UnsupportedRegion(name) => write!(f, "{name} runs several passes over the whole texture and can't be queued with a region"),
+            Self::Unsupported3d(name) => write!(f, "{name} only supports 2D textures"),
+        }
+    }
+}
+
+impl std::error::Error for ComputeNoiseError {}
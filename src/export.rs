@@ -0,0 +1,277 @@
+use std::{error::Error, path::{Path, PathBuf}, sync::{atomic::{AtomicUsize, Ordering}, Arc, Mutex}};
+
+use bevy::{prelude::*, tasks::IoTaskPool};
+use image::{ImageBuffer, ImageFormat, Rgba};
+
+use crate::{error::ComputeNoiseError, image::ComputeNoiseSize, readback::ComputeNoiseReadbackQueue};
+
+/// File formats noise can be saved as.
+///
+/// Noise is generated as `Rgba8Unorm`, so 16-bit PNGs and EXRs hold the same values in a wider
+/// format for tools that expect one.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum NoiseFileFormat {
+    /// 8-bit RGBA PNG, 2D textures only.
+    Png,
+    /// 16-bit RGBA PNG, 2D textures only.
+    Png16,
+    /// 32-bit float RGBA OpenEXR, 2D textures only.
+    Exr,
+    /// Uncompressed `R8G8B8A8_UNORM` KTX2, 3D, array and cube textures are kept as they are.
+    Ktx2,
+    /// 8-bit RGBA PNG with the z slices of a 3D texture, or the layers of a layered texture,
+    /// laid out left to right then top to bottom, `columns` slices wide.
+    PngAtlas { columns: u32 },
+}
+
+impl NoiseFileFormat {
+    fn supports(&self, size: ComputeNoiseSize) -> bool {
+        match self {
+            Self::Png | Self::Png16 | Self::Exr => matches!(size, ComputeNoiseSize::D2(..)),
+            Self::Ktx2 | Self::PngAtlas { .. } => true,
+        }
+    }
+}
+
+struct SaveRequest {
+    image: Handle<Image>,
+    path: PathBuf,
+    format: NoiseFileFormat,
+}
+
+/// Shared between the render and main worlds.
+#[derive(Default)]
+struct SaveState {
+    /// Images read back from the GPU, waiting to be written.
+    completed: Mutex<Vec<(SaveRequest, ComputeNoiseSize, Vec<u8>)>>,
+    /// Saves that haven't been written or failed yet.
+    pending: AtomicUsize,
+    /// Saves that couldn't be read back or written.
+    failed: AtomicUsize,
+}
+
+impl SaveState {
+    fn finish(&self) {
+        self.pending.fetch_sub(1, Ordering::AcqRel);
+    }
+
+    fn fail(&self) {
+        self.failed.fetch_add(1, Ordering::AcqRel);
+        self.finish();
+    }
+}
+
+#[derive(Resource, Default)]
+pub struct ComputeNoiseSaveQueue {
+    requested: Vec<SaveRequest>,
+    state: Arc<SaveState>,
+}
+
+impl ComputeNoiseSaveQueue {
+    /// Reads `image` back from the GPU and writes it to `path`.
+    ///
+    /// The image is read once all the noise queued into it has been written, so this can be called
+    /// straight after queuing. Files are written in the background and errors are logged.
+    pub fn save_noise_image(&mut self, image: Handle<Image>, path: impl Into<PathBuf>, format: NoiseFileFormat) {
+        self.state.pending.fetch_add(1, Ordering::AcqRel);
+        self.requested.push(SaveRequest { image, path: path.into(), format });
+    }
+
+    /// Number of saves that are still being read back or written.
+    pub fn pending(&self) -> usize {
+        self.state.pending.load(Ordering::Acquire)
+    }
+
+    /// Number of saves that couldn't be read back or written, they're logged as errors when they fail.
+    pub fn failed(&self) -> usize {
+        self.state.failed.load(Ordering::Acquire)
+    }
+}
+
+pub(crate) fn request_compute_noise_saves(
+    mut save_queue: ResMut<ComputeNoiseSaveQueue>,
+    mut readback_queue: ResMut<ComputeNoiseReadbackQueue>,
+) {
+    let save_queue = &mut *save_queue;
+    for request in save_queue.requested.drain(..) {
+        let state = save_queue.state.clone();
+        readback_queue.read(request.image.clone(), move |size, data| {
+            let Some(data) = data else { return state.fail() };
+            if !request.format.supports(size) {
+                error!("{} - did not save {}.", ComputeNoiseError::UnsupportedExport, request.path.display());
+                return state.fail();
+            }
+            state.completed.lock().unwrap().push((request, size, data));
+        });
+    }
+}
+
+pub(crate) fn save_compute_noise_images(save_queue: Res<ComputeNoiseSaveQueue>) {
+    let completed = std::mem::take(&mut *save_queue.state.completed.lock().unwrap());
+    for (request, size, data) in completed {
+        let state = save_queue.state.clone();
+        IoTaskPool::get().spawn(async move {
+            match write_noise_file(&request.path, request.format, size, &data) {
+                Ok(()) => {
+                    info!("Saved compute noise to {}", request.path.display());
+                    state.finish();
+                },
+                Err(error) => {
+                    error!("Failed to save compute noise to {}: {error}", request.path.display());
+                    state.fail();
+                },
+            }
+        }).detach();
+    }
+}
+
+fn write_noise_file(path: &Path, format: NoiseFileFormat, size: ComputeNoiseSize, data: &[u8]) -> Result<(), Box<dyn Error + Send + Sync>> {
+    let (width, height) = (size.width(), size.height());
+    match format {
+        NoiseFileFormat::Png => {
+            ImageBuffer::<Rgba<u8>, Vec<u8>>::from_raw(width, height, data.to_vec())
+                .ok_or("readback doesn't match the image size")?
+                .save_with_format(path, ImageFormat::Png)?;
+        },
+        NoiseFileFormat::Png16 => {
+            ImageBuffer::<Rgba<u16>, Vec<u16>>::from_raw(width, height, data.iter().map(|value| *value as u16 * 257).collect())
+                .ok_or("readback doesn't match the image size")?
+                .save_with_format(path, ImageFormat::Png)?;
+        },
+        NoiseFileFormat::Exr => {
+            ImageBuffer::<Rgba<f32>, Vec<f32>>::from_raw(width, height, data.iter().map(|value| *value as f32 / 255.0).collect())
+                .ok_or("readback doesn't match the image size")?
+                .save_with_format(path, ImageFormat::OpenExr)?;
+        },
+        NoiseFileFormat::Ktx2 => std::fs::write(path, encode_ktx2(size, data))?,
+        NoiseFileFormat::PngAtlas { columns } => {
+            let (atlas_width, atlas_height, atlas) = slice_atlas(size, data, columns);
+            ImageBuffer::<Rgba<u8>, Vec<u8>>::from_raw(atlas_width, atlas_height, atlas)
+                .ok_or("readback doesn't match the image size")?
+                .save_with_format(path, ImageFormat::Png)?;
+        },
+    }
+    Ok(())
+}
+
+/// Lays the slices or layers in `data` out in a grid `columns` slices wide.
+fn slice_atlas(size: ComputeNoiseSize, data: &[u8], columns: u32) -> (u32, u32, Vec<u8>) {
+    let (width, height) = (size.width() as usize, size.height() as usize);
+    let slices = (size.depth() * size.layers()) as usize;
+    let columns = (columns as usize).clamp(1, slices);
+    let rows = slices.div_ceil(columns);
+
+    let atlas_row_len = width * columns * 4;
+    let mut atlas = vec![0; atlas_row_len * height * rows];
+    for (slice, texels) in data.chunks(width * height * 4).enumerate() {
+        let (column, row) = (slice % columns, slice / columns);
+        for (y, texel_row) in texels.chunks(width * 4).enumerate() {
+            let start = (row * height + y) * atlas_row_len + column * width * 4;
+            atlas[start..start + width * 4].copy_from_slice(texel_row);
+        }
+    }
+
+    ((width * columns) as u32, (height * rows) as u32, atlas)
+}
+
+/// Writes a single mip level KTX2 file with a basic data format descriptor, no supercompression
+/// and no key/value data.
+fn encode_ktx2(size: ComputeNoiseSize, data: &[u8]) -> Vec<u8> {
+    const IDENTIFIER: [u8; 12] = [0xAB, 0x4B, 0x54, 0x58, 0x20, 0x32, 0x30, 0xBB, 0x0D, 0x0A, 0x1A, 0x0A];
+    const VK_FORMAT_R8G8B8A8_UNORM: u32 = 37;
+    const HEADER_LEN: u32 = 12 + 9 * 4 + 4 * 4 + 2 * 8 + 3 * 8;
+
+    let (depth, layers, faces) = match size {
+        ComputeNoiseSize::D2(..) => (0, 0, 1),
+        ComputeNoiseSize::D3(_, _, depth) => (depth, 0, 1),
+        ComputeNoiseSize::D2Array(_, _, layers) => (0, layers, 1),
+        ComputeNoiseSize::Cube(_) => (0, 0, 6),
+    };
+
+    // Basic descriptor block: RGBSDA color model, BT.709 primaries, linear transfer, one 8-bit sample per channel.
+    let mut dfd = vec![
+        0,
+        (24 + 16 * 4) << 16 | 2,
+        1 | 1 << 8 | 1 << 16,
+        0,
+        4,
+        0,
+    ];
+    for (channel, bit_offset) in [(0, 0), (1, 8), (2, 16), (15, 24)] {
+        dfd.extend([bit_offset | 7 << 16 | channel << 24, 0, 0, 255]);
+    }
+    let dfd_len = 4 + dfd.len() as u32 * 4;
+
+    let mut file = Vec::with_capacity((HEADER_LEN + dfd_len) as usize + data.len());
+    file.extend(IDENTIFIER);
+    for value in [VK_FORMAT_R8G8B8A8_UNORM, 1, size.width(), size.height(), depth, layers, faces, 1, 0] {
+        file.extend(value.to_le_bytes());
+    }
+    for value in [HEADER_LEN, dfd_len, 0, 0] {
+        file.extend(value.to_le_bytes());
+    }
+    let level_offset = (HEADER_LEN + dfd_len) as u64;
+    for value in [0, 0, level_offset, data.len() as u64, data.len() as u64] {
+        file.extend(value.to_le_bytes());
+    }
+    file.extend(dfd_len.to_le_bytes());
+    for value in dfd {
+        file.extend(u32::to_le_bytes(value));
+    }
+    file.extend(data);
+
+    file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn slice_atlas_layout() {
+        let size = ComputeNoiseSize::D3(2, 1, 3);
+        let data: Vec<u8> = (0..3).flat_map(|slice| [slice; 8]).collect();
+
+        let (width, height, atlas) = slice_atlas(size, &data, 2);
+        assert_eq!((width, height), (4, 2));
+        assert_eq!(&atlas[..16], &[0; 8].into_iter().chain([1; 8]).collect::<Vec<u8>>()[..]);
+        assert_eq!(&atlas[16..24], &[2; 8]);
+        assert_eq!(&atlas[24..], &[0; 8]);
+
+        assert_eq!(slice_atlas(size, &data, 0).0, 2);
+    }
+
+    #[test]
+    fn ktx2_header() {
+        let size = ComputeNoiseSize::D3(4, 4, 2);
+        let data = vec![7; size.data_len() * 4];
+        let file = encode_ktx2(size, &data);
+
+        let read_u32 = |offset: usize| u32::from_le_bytes(file[offset..offset + 4].try_into().unwrap());
+        let read_u64 = |offset: usize| u64::from_le_bytes(file[offset..offset + 8].try_into().unwrap());
+        assert_eq!(read_u32(12), 37);
+        assert_eq!((read_u32(20), read_u32(24), read_u32(28)), (4, 4, 2));
+        assert_eq!((read_u32(40), read_u32(44)), (1, 0));
+
+        let (dfd_offset, dfd_len) = (read_u32(48) as usize, read_u32(52) as usize);
+        assert_eq!(dfd_offset, 104);
+        assert_eq!(read_u32(dfd_offset), dfd_len as u32);
+
+        let (level_offset, level_len) = (read_u64(80) as usize, read_u64(88) as usize);
+        assert_eq!(level_offset, dfd_offset + dfd_len);
+        assert_eq!(level_offset % 4, 0);
+        assert_eq!(&file[level_offset..level_offset + level_len], &data[..]);
+    }
+
+    #[test]
+    fn save_state_counts_failures() {
+        let mut save_queue = ComputeNoiseSaveQueue::default();
+        save_queue.save_noise_image(Handle::default(), "written.png", NoiseFileFormat::Png);
+        save_queue.save_noise_image(Handle::default(), "failed.png", NoiseFileFormat::Png);
+        assert_eq!((save_queue.pending(), save_queue.failed()), (2, 0));
+
+        save_queue.state.finish();
+        save_queue.state.fail();
+        assert_eq!((save_queue.pending(), save_queue.failed()), (0, 1));
+    }
+}
@@ -1,4 +1,4 @@
-use bevy::{prelude::*, render::{render_asset::RenderAssetUsages, render_resource::{Extent3d, TextureDimension, TextureFormat, TextureUsages}}};
+use bevy::{image::{ImageAddressMode, ImageFilterMode, ImageSampler, ImageSamplerDescriptor}, prelude::*, render::{render_asset::RenderAssetUsages, render_resource::{Extent3d, TextureDimension, TextureFormat, TextureUsages, TextureViewDescriptor, TextureViewDimension}}};
 
 pub struct ComputeNoiseImage;
 
@@ -18,14 +18,57 @@ impl ComputeNoiseImage {
             | TextureUsages::STORAGE_BINDING
             | TextureUsages::TEXTURE_BINDING;
 
+        image.texture_view_descriptor = match size {
+            ComputeNoiseSize::D2Array(..) | ComputeNoiseSize::Cube(_) => Some(TextureViewDescriptor {
+                dimension: Some(size.into()),
+                ..default()
+            }),
+            _ => None,
+        };
+
         image
     }
+
+    /// Creates an image with a full mip chain, which is downsampled again after every sequence that writes to it.
+    /// Tileable images sample with repeat addressing and wrap around the edges while downsampling.
+    pub fn create_mipmapped_image(size: ComputeNoiseSize, tileable: bool) -> Image {
+        let mut image = Self::create_image(size);
+        image.texture_descriptor.mip_level_count = size.mip_level_count();
+
+        // Every level gets uploaded, so the data has to cover the whole chain.
+        let texels: usize = (0..size.mip_level_count()).map(|level| size.mip_size(level).data_len()).sum();
+        image.data = vec![0; texels * 4];
+
+        let address_mode = if tileable { ImageAddressMode::Repeat } else { ImageAddressMode::ClampToEdge };
+        image.sampler = ImageSampler::Descriptor(ImageSamplerDescriptor {
+            address_mode_u: address_mode,
+            address_mode_v: address_mode,
+            address_mode_w: address_mode,
+            mag_filter: ImageFilterMode::Linear,
+            min_filter: ImageFilterMode::Linear,
+            mipmap_filter: ImageFilterMode::Linear,
+            ..default()
+        });
+
+        image
+    }
+
+    /// Whether mips of `image` should wrap around the edges while downsampling.
+    pub(crate) fn is_tileable(image: &Image) -> bool {
+        matches!(&image.sampler, ImageSampler::Descriptor(ImageSamplerDescriptor { address_mode_u: ImageAddressMode::Repeat, .. }))
+    }
 }
 
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum ComputeNoiseSize {
     D2(u32, u32),
     D3(u32, u32, u32),
+    /// Width, height and number of layers. Generators offset their seed by the layer index,
+    /// modifiers are applied to each layer separately.
+    D2Array(u32, u32, u32),
+    /// Width and height of each face. Generators sample 3D noise on the unit sphere so the faces
+    /// line up, modifiers are applied to each face separately.
+    Cube(u32),
 }
 
 impl ComputeNoiseSize {
@@ -33,6 +76,8 @@ impl ComputeNoiseSize {
         match self {
             Self::D2(width, _) => *width,
             Self::D3(width, _, _) => *width,
+            Self::D2Array(width, _, _) => *width,
+            Self::Cube(size) => *size,
         }
     }
 
@@ -40,52 +85,125 @@ impl ComputeNoiseSize {
         match self {
             Self::D2(_, height) => *height,
             Self::D3(_, height, _) => *height,
+            Self::D2Array(_, height, _) => *height,
+            Self::Cube(size) => *size,
         }
     }
 
     pub fn depth(&self) -> u32 {
         match self {
-            Self::D2(_, _) => 1,
             Self::D3(_, _, depth) => *depth,
+            _ => 1,
         }
     }
 
+    /// Number of array layers, 6 for cubemaps.
+    pub fn layers(&self) -> u32 {
+        match self {
+            Self::D2Array(_, _, layers) => *layers,
+            Self::Cube(_) => 6,
+            _ => 1,
+        }
+    }
+
+    /// Whether the layers are bound as a 2D array and dispatched one at a time.
+    pub(crate) fn is_layered(&self) -> bool {
+        matches!(self, Self::D2Array(..) | Self::Cube(_))
+    }
+
     pub fn data_len(&self) -> usize {
+        (self.width() * self.height() * self.depth() * self.layers()) as usize
+    }
+
+    /// Number of mips down to 1x1, or 1x1x1 for 3D textures.
+    pub fn mip_level_count(&self) -> u32 {
+        let largest = self.width().max(self.height()).max(self.depth()).max(1);
+        u32::BITS - largest.leading_zeros()
+    }
+
+    /// Size of mip `level`, layers aren't downsampled.
+    pub fn mip_size(&self, level: u32) -> Self {
+        let mip = |size: u32| (size >> level).max(1);
+        match *self {
+            Self::D2(width, height) => Self::D2(mip(width), mip(height)),
+            Self::D3(width, height, depth) => Self::D3(mip(width), mip(height), mip(depth)),
+            Self::D2Array(width, height, layers) => Self::D2Array(mip(width), mip(height), layers),
+            Self::Cube(size) => Self::Cube(mip(size)),
+        }
+    }
+
+    /// Workgroup size the shaders use for this dimension, kept within the default
+    /// `max_compute_invocations_per_workgroup` of 256.
+    pub(crate) fn workgroup_size(&self) -> (u32, u32, u32) {
         match self {
-            Self::D2(width, height) => (width * height) as usize,
-            Self::D3(width, height, depth) => (width * height * depth) as usize, 
+            Self::D3(_, _, _) => (8, 8, 4),
+            _ => (16, 16, 1),
         }
     }
 
+    /// Workgroups needed to cover the texture, or a single layer of a layered texture.
     pub(crate) fn workgroup_count(&self) -> (u32, u32, u32) {
-        match self {
-            Self::D2(width, height) => (width / 32, height / 32, 1),
-            Self::D3(width, height, depth) => (width / 8, height / 8, depth / 8),
+        let (x, y, z) = self.workgroup_size();
+        (
+            self.width().div_ceil(x),
+            self.height().div_ceil(y),
+            self.depth().div_ceil(z),
+        )
+    }
+}
+
+/// Box of texels to write, the rest of the texture is left untouched. Shaders still see the whole
+/// texture, so `uv` lines up with noise written to the full texture. On layered textures only
+/// `x` and `y` are used and every layer is written.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ComputeNoiseRegion {
+    pub origin: UVec3,
+    pub extent: UVec3,
+}
+
+impl ComputeNoiseRegion {
+    pub fn new(origin: UVec3, extent: UVec3) -> Self {
+        Self { origin, extent }
+    }
+
+    /// Region of a 2D texture.
+    pub fn rect(origin: UVec2, extent: UVec2) -> Self {
+        Self { origin: origin.extend(0), extent: extent.extend(1) }
+    }
+
+    /// Start and end of `region` within a texture of `size`, the whole texture if there's no region.
+    pub(crate) fn bounds(region: Option<Self>, size: ComputeNoiseSize) -> (UVec3, UVec3) {
+        let full = UVec3::new(size.width(), size.height(), size.depth());
+        let Some(region) = region else { return (UVec3::ZERO, full) };
+
+        let mut start = region.origin.min(full);
+        let mut end = region.origin.saturating_add(region.extent).min(full);
+        if size.is_layered() {
+            start.z = 0;
+            end.z = 1;
         }
+        (start, end.max(start))
+    }
+
+    /// Texels of mip `level` that downsampling this region changes.
+    pub(crate) fn mip(&self, level: u32) -> Self {
+        let start = self.origin >> level;
+        let end = (self.origin.saturating_add(self.extent) + (1 << level) - 1) >> level;
+        Self { origin: start, extent: end - start }
     }
 }
 
 impl From<ComputeNoiseSize> for Extent3d {
     fn from(value: ComputeNoiseSize) -> Self {
-        match value {
-            ComputeNoiseSize::D2(width, height) => {
-                Extent3d {
-                    width,
-                    height,
-                    depth_or_array_layers: 1,
-                }
-            },
-            ComputeNoiseSize::D3(width, height, depth) => {
-                Extent3d {
-                    width,
-                    height,
-                    depth_or_array_layers: depth,
-                }
-            }
+        Extent3d {
+            width: value.width(),
+            height: value.height(),
+            depth_or_array_layers: value.depth() * value.layers(),
         }
     }
 }
 
+/// Only tells 2D and 3D apart, use `From<&Image>` to get array and cube sizes.
 impl From<Extent3d> for ComputeNoiseSize {
     fn from(value: Extent3d) -> Self {
         if value.depth_or_array_layers == 1 {
@@ -96,11 +214,114 @@ impl From<Extent3d> for ComputeNoiseSize {
     }
 }
 
+impl From<&Image> for ComputeNoiseSize {
+    fn from(image: &Image) -> Self {
+        let size = image.texture_descriptor.size;
+        if image.texture_descriptor.dimension == TextureDimension::D3 {
+            return ComputeNoiseSize::D3(size.width, size.height, size.depth_or_array_layers);
+        }
+
+        let view_dimension = image.texture_view_descriptor.as_ref().and_then(|descriptor| descriptor.dimension);
+        match view_dimension {
+            Some(TextureViewDimension::Cube) => ComputeNoiseSize::Cube(size.width),
+            Some(TextureViewDimension::D2Array | TextureViewDimension::CubeArray) => {
+                ComputeNoiseSize::D2Array(size.width, size.height, size.depth_or_array_layers)
+            },
+            _ if size.depth_or_array_layers > 1 => {
+                ComputeNoiseSize::D2Array(size.width, size.height, size.depth_or_array_layers)
+            },
+            _ => ComputeNoiseSize::D2(size.width, size.height),
+        }
+    }
+}
+
 impl From<ComputeNoiseSize> for TextureDimension {
     fn from(value: ComputeNoiseSize) -> Self {
         match value {
-            ComputeNoiseSize::D2(_, _) => TextureDimension::D2,
             ComputeNoiseSize::D3(_, _, _) => TextureDimension::D3,
+            _ => TextureDimension::D2,
+        }
+    }
+}
+
+impl From<ComputeNoiseSize> for TextureViewDimension {
+    fn from(value: ComputeNoiseSize) -> Self {
+        match value {
+            ComputeNoiseSize::D2(_, _) => TextureViewDimension::D2,
+            ComputeNoiseSize::D3(_, _, _) => TextureViewDimension::D3,
+            ComputeNoiseSize::D2Array(_, _, _) => TextureViewDimension::D2Array,
+            ComputeNoiseSize::Cube(_) => TextureViewDimension::Cube,
         }
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn workgroup_count_covers_odd_sizes() {
+        assert_eq!(ComputeNoiseSize::D2(512, 512).workgroup_count(), (32, 32, 1));
+        assert_eq!(ComputeNoiseSize::D2(100, 100).workgroup_count(), (7, 7, 1));
+        assert_eq!(ComputeNoiseSize::D2(1, 17).workgroup_count(), (1, 2, 1));
+        assert_eq!(ComputeNoiseSize::D3(128, 128, 128).workgroup_count(), (16, 16, 32));
+        assert_eq!(ComputeNoiseSize::D3(100, 33, 5).workgroup_count(), (13, 5, 2));
+        assert_eq!(ComputeNoiseSize::D2Array(100, 100, 8).workgroup_count(), (7, 7, 1));
+        assert_eq!(ComputeNoiseSize::Cube(64).workgroup_count(), (4, 4, 1));
+    }
+
+    #[test]
+    fn size_from_layered_images() {
+        for size in [
+            ComputeNoiseSize::D2(64, 32),
+            ComputeNoiseSize::D3(16, 16, 16),
+            ComputeNoiseSize::D2Array(32, 32, 4),
+            ComputeNoiseSize::D2Array(32, 32, 6),
+            ComputeNoiseSize::Cube(32),
+        ] {
+            assert_eq!(ComputeNoiseSize::from(&ComputeNoiseImage::create_image(size)), size);
+        }
+    }
+
+    #[test]
+    fn mip_chain_sizes() {
+        assert_eq!(ComputeNoiseSize::D2(512, 512).mip_level_count(), 10);
+        assert_eq!(ComputeNoiseSize::D2(300, 17).mip_level_count(), 9);
+        assert_eq!(ComputeNoiseSize::D3(32, 32, 128).mip_level_count(), 8);
+        assert_eq!(ComputeNoiseSize::D2(1, 1).mip_level_count(), 1);
+        assert_eq!(ComputeNoiseSize::D3(32, 32, 128).mip_size(6), ComputeNoiseSize::D3(1, 1, 2));
+        assert_eq!(ComputeNoiseSize::D2Array(64, 16, 4).mip_size(5), ComputeNoiseSize::D2Array(2, 1, 4));
+
+        let image = ComputeNoiseImage::create_mipmapped_image(ComputeNoiseSize::D2(4, 2), true);
+        assert_eq!(image.texture_descriptor.mip_level_count, 3);
+        assert_eq!(image.data.len(), (8 + 2 + 1) * 4);
+        assert!(ComputeNoiseImage::is_tileable(&image));
+        assert!(!ComputeNoiseImage::is_tileable(&ComputeNoiseImage::create_mipmapped_image(ComputeNoiseSize::D2(4, 2), false)));
+    }
+
+    #[test]
+    fn region_bounds() {
+        let size = ComputeNoiseSize::D2(256, 256);
+        assert_eq!(ComputeNoiseRegion::bounds(None, size), (UVec3::ZERO, UVec3::new(256, 256, 1)));
+        assert_eq!(
+            ComputeNoiseRegion::bounds(Some(ComputeNoiseRegion::rect(UVec2::new(200, 16), UVec2::new(100, 32))), size),
+            (UVec3::new(200, 16, 0), UVec3::new(256, 48, 1)),
+        );
+        assert_eq!(
+            ComputeNoiseRegion::bounds(Some(ComputeNoiseRegion::new(UVec3::new(8, 8, 4), UVec3::splat(8))), ComputeNoiseSize::Cube(64)),
+            (UVec3::new(8, 8, 0), UVec3::new(16, 16, 1)),
+        );
+
+        let region = ComputeNoiseRegion::rect(UVec2::new(3, 4), UVec2::new(6, 4));
+        assert_eq!(region.mip(1), ComputeNoiseRegion::new(UVec3::new(1, 2, 0), UVec3::new(4, 2, 1)));
+        assert_eq!(region.mip(3), ComputeNoiseRegion::new(UVec3::new(0, 0, 0), UVec3::new(2, 1, 1)));
+    }
+
+    #[test]
+    fn workgroup_size_within_default_limit() {
+        for size in [ComputeNoiseSize::D2(1, 1), ComputeNoiseSize::D3(1, 1, 1)] {
+            let (x, y, z) = size.workgroup_size();
+            assert!(x * y * z <= 256);
+        }
+    }
+}
@@ -1,36 +1,58 @@
 use std::marker::PhantomData;
 
 use bevy::{
-    asset::embedded_asset, prelude::*, render::{render_resource::SpecializedComputePipelines, Render, RenderApp, RenderSet}
+    asset::embedded_asset, prelude::*, render::{extract_resource::ExtractResourcePlugin, graph::CameraDriverLabel, render_graph::RenderGraph, render_resource::SpecializedComputePipelines, Render, RenderApp, RenderSet}
 };
-use crate::noise::modifiers::{invert::Invert, ComputeNoiseModifier};
-use noise::generators::{Perlin, Worley};
+use crate::noise::modifiers::{distance_field::{DistanceField, DistanceFieldPass}, downsample::Downsample, erosion::{ErosionPass, HydraulicErosion, ThermalErosion}, gradient_map::GradientMap, invert::Invert, morphology::{Morphology, MorphologyPass}, threshold::{SmoothStep, Threshold}, ComputeNoiseModifier};
+use noise::generators::{expression::{prepare_expression_shaders, report_expression_errors, send_expression_errors, ExpressionErrors, ExpressionNoise, ExpressionNoiseError, ExpressionShaders}, blue_noise::{BlueNoise, BlueNoisePass}, CloudDetail, CloudNoise, CloudShape, Fbm, Perlin, Spherical, White, Worley, WrapperShaders};
+use buffer_pool::ComputeNoiseBufferPool;
+use settings::ComputeNoiseSettings;
+use terrain::{build_noise_terrain, finish_noise_terrain_readbacks, gpu::{extract_terrain_displacements, prepare_terrain_displacements, NoiseTerrainDisplacements, PendingTerrainDisplacements, TerrainDisplacementPipeline}, NoiseTerrain};
+use readback::{extract_compute_noise_readbacks, map_compute_noise_readbacks, prepare_compute_noise_readbacks, ComputeNoiseReadbackQueue, ComputeNoiseReadbacks};
 use noise_queue::{prepare_compute_noise_buffers, ComputeNoiseBufferQueue};
-use render::{compute::{compute_noise, submit_compute_noise, ComputeNoiseEncoder}, pipeline::{load_generator_shader, load_fbm_shaders, load_modifier_shader, ComputeNoisePipeline}};
+use render::{compute::{compute_noise, prepare_compute_noise_dispatches, submit_compute_noise, ComputeNoiseDispatches, ComputeNoiseEncoder}, pipeline::{load_generator_shader, load_blue_noise_shaders, load_cloud_shaders, load_fbm_shaders, load_expression_shaders, load_modifier_shader, load_spherical_shaders, load_white_shaders, warm_up_pipelines, ComputeNoisePipeline, ComputeNoiseWarmUp}};
 
 use crate::{
-    noise::generators::ComputeNoiseGenerator,
-    noise_queue::{ComputeNoiseQueue, ComputeNoiseRenderQueue},
+    noise::{generators::ComputeNoiseGenerator, ComputeNoise},
+    noise_queue::{ComputeNoisePipelinesReady, ComputeNoiseQueue, ComputeNoiseRenderQueue},
     render::{
-        extract::extract_compute_noise_queue,
-        prepare::prepare_render_noise,
+        extract::{extract_compute_noise_queue, extract_compute_noise_warm_up},
+        prepare::{prepare_render_noise, ComputeNoiseBindGroupCache},
     },
 };
 
+pub mod buffer_pool;
+pub mod error;
+#[cfg(feature = "export")]
+pub mod export;
 pub mod image;
+#[cfg(feature = "material")]
+pub mod material;
 pub mod noise;
 pub mod noise_queue;
+pub mod noise_texture;
+mod readback;
 mod render;
+pub mod settings;
 mod shader;
+pub mod terrain;
+
+pub use render::node::{ComputeNoiseLabel, ComputeNoiseNode};
 
 pub mod prelude {
     pub use crate::{
-        image::{ComputeNoiseImage, ComputeNoiseSize},
-        noise::generators::{Worley, Perlin, PerlinFlags, WorleyFlags, Fbm},
-        noise::modifiers::Invert,
-        noise_queue::ComputeNoiseQueue,
+        error::ComputeNoiseError,
+        image::{ComputeNoiseImage, ComputeNoiseRegion, ComputeNoiseSize},
+        noise::generators::{Worley, Perlin, PerlinFlags, WorleyFlags, Fbm, Spherical, ExpressionNoise, ExpressionNoiseError, CloudNoise, CloudNoiseImages, CloudShape, CloudDetail, White, BlueNoise, BlueNoiseFlags},
+        noise::modifiers::{Invert, ThermalErosion, HydraulicErosion, ErosionFlags, DistanceField, DistanceFieldFlags, GradientMap, GradientInterpolation, Threshold, ThresholdFlags, SmoothStep, Morphology, MorphologyOperation, StructuringElement, MorphologyFlags},
+        noise_queue::{ComputeNoiseQueue, ComputeNoisePipelinesReady},
+        noise_texture::{NoiseTexture, NoiseTexturePlugin},
+        settings::{ComputeNoiseSettings, ComputeNoiseDispatchMode},
+        terrain::{NoiseTerrain, NoiseHeightmap},
         ComputeNoisePlugin
     };
+    #[cfg(feature = "material")]
+    pub use crate::material::{NoiseMaterial, NoiseMaterialExtension, MaterialNoise, NoiseMaterialFlags, TriplanarMaterial, TriplanarMaterialExtension};
 }
 
 #[derive(Default)]
@@ -39,13 +61,30 @@ pub struct ComputeNoiseGeneratorPlugin<T: ComputeNoiseGenerator>(PhantomData<T>)
 impl<T: ComputeNoiseGenerator> Plugin for ComputeNoiseGeneratorPlugin<T> {
     fn build(&self, app: &mut App) {
         T::embed_shaders(app);
-        app.register_type::<T>();
+        app
+            .register_type::<T>()
+            .register_type::<Fbm<T>>()
+            .register_type::<Spherical<T>>()
+            .init_resource::<ComputeNoiseQueue>();
+
+        let mut noise_queue = app.world_mut().resource_mut::<ComputeNoiseQueue>();
+        noise_queue.register::<T>();
+        noise_queue.register::<Fbm<T>>();
+        noise_queue.register::<Spherical<T>>();
    }
 
     fn finish(&self, app: &mut App) {
+        let wrappers = WrapperShaders::new::<T>(&mut app.world_mut().resource_mut::<Assets<Shader>>());
+
         let render_app = app.sub_app_mut(RenderApp);
-        load_generator_shader::<T>(render_app.world_mut());
-        load_fbm_shaders::<T>(render_app.world_mut());
+        load_generator_shader::<T>(render_app.world_mut(), wrappers.as_ref().ok());
+        match wrappers {
+            Ok(wrappers) => {
+                load_fbm_shaders::<T>(render_app.world_mut(), &wrappers);
+                load_spherical_shaders::<T>(render_app.world_mut(), &wrappers);
+            }
+            Err(error) => error!("{error} - did not load its wrapper shaders."),
+        }
     }
 }
 
@@ -54,6 +93,10 @@ pub struct ComputeNoiseModificationPlugin<T: ComputeNoiseModifier>(PhantomData<T
 impl<T: ComputeNoiseModifier> Plugin for ComputeNoiseModificationPlugin<T> {
     fn build(&self, app: &mut App) {
         T::embed_shaders(app);
+        app
+            .register_type::<T>()
+            .init_resource::<ComputeNoiseQueue>()
+            .world_mut().resource_mut::<ComputeNoiseQueue>().register::<T>();
     }
 
     fn finish(&self, app: &mut App) { 
@@ -62,45 +105,153 @@ impl<T: ComputeNoiseModifier> Plugin for ComputeNoiseModificationPlugin<T> {
     }
 }
 
-// TODO: take in shader so can use custom noise with fbm
-// pub struct ComputeNoisePlugin {
-//     fbm_shader: &'static str,
-// }
+/// Registers noise that loads its own shaders instead of going through a generator or modifier plugin,
+/// see `load_cloud_shaders`.
+fn register_noise<T: ComputeNoise>(app: &mut App) {
+    app
+        .register_type::<T>()
+        .init_resource::<ComputeNoiseQueue>()
+        .world_mut().resource_mut::<ComputeNoiseQueue>().register::<T>();
+}
+
 pub struct ComputeNoisePlugin;
 impl Plugin for ComputeNoisePlugin {
     fn build(&self, app: &mut App) {
         embedded_asset!(app, "noise/shaders/util.wgsl");
-        embedded_asset!(app, "noise/generators/shaders/fbm.wgsl");
+        embedded_asset!(app, "noise/shaders/dispatch.wgsl");
+        embedded_asset!(app, "noise/shaders/math.wgsl");
+        embedded_asset!(app, "noise/shaders/fbm.wgsl");
+        embedded_asset!(app, "noise/generators/shaders/perlin.wgsl");
+        embedded_asset!(app, "noise/generators/shaders/worley.wgsl");
+        embedded_asset!(app, "noise/generators/shaders/cloud.wgsl");
+        embedded_asset!(app, "noise/generators/shaders/white.wgsl");
+        embedded_asset!(app, "noise/generators/shaders/blue_noise.wgsl");
+        embedded_asset!(app, "noise/shaders/triplanar.wgsl");
+        embedded_asset!(app, "terrain/shaders/displace_terrain.wgsl");
 
         app
             .add_plugins((
                 ComputeNoiseGeneratorPlugin::<Perlin>::default(),
                 ComputeNoiseGeneratorPlugin::<Worley>::default(),
                 ComputeNoiseModificationPlugin::<Invert>::default(),
+                ComputeNoiseModificationPlugin::<ErosionPass>::default(),
+                ComputeNoiseModificationPlugin::<DistanceFieldPass>::default(),
+                ComputeNoiseModificationPlugin::<GradientMap>::default(),
+                ComputeNoiseModificationPlugin::<Threshold>::default(),
+                ComputeNoiseModificationPlugin::<SmoothStep>::default(),
+                ComputeNoiseModificationPlugin::<MorphologyPass>::default(),
+                ComputeNoiseModificationPlugin::<Downsample>::default(),
             ))
             .init_resource::<ComputeNoiseQueue>()
             .init_resource::<ComputeNoiseBufferQueue>()
+            .init_resource::<ComputeNoiseSettings>()
+            .init_resource::<ComputeNoisePipelinesReady>()
+            .add_plugins(ExtractResourcePlugin::<ComputeNoiseSettings>::default())
             .add_systems(PostUpdate, prepare_compute_noise_buffers);
 
+        // Modifiers that expand into several passes aren't registered by their plugin, and serializing
+        // the generic wrappers needs the generator to be serializable, so it's only registered for these.
+        app
+            .register_type::<ThermalErosion>()
+            .register_type::<HydraulicErosion>()
+            .register_type::<DistanceField>()
+            .register_type::<Morphology>()
+            .register_type_data::<Fbm<Perlin>, ReflectSerialize>()
+            .register_type_data::<Fbm<Perlin>, ReflectDeserialize>()
+            .register_type_data::<Fbm<Worley>, ReflectSerialize>()
+            .register_type_data::<Fbm<Worley>, ReflectDeserialize>()
+            .register_type_data::<Spherical<Perlin>, ReflectSerialize>()
+            .register_type_data::<Spherical<Perlin>, ReflectDeserialize>()
+            .register_type_data::<Spherical<Worley>, ReflectSerialize>()
+            .register_type_data::<Spherical<Worley>, ReflectDeserialize>();
+
+        let expression_errors = ExpressionErrors::default();
+        app
+            .register_type::<ExpressionNoise>()
+            .add_event::<ExpressionNoiseError>()
+            .init_resource::<ExpressionShaders>()
+            .insert_resource(expression_errors.clone())
+            .add_plugins(ExtractResourcePlugin::<ExpressionShaders>::default())
+            .add_systems(PreUpdate, send_expression_errors)
+            .add_systems(PostUpdate, prepare_expression_shaders.before(prepare_compute_noise_buffers))
+            .world_mut().resource_mut::<ComputeNoiseQueue>().register::<ExpressionNoise>();
+
+        app.register_type::<CloudNoise>();
+        register_noise::<CloudShape>(app);
+        register_noise::<CloudDetail>(app);
+
+        // Blue noise is queued as passes like the multi-pass modifiers, so the passes are registered instead.
+        app.register_type::<BlueNoise>();
+        register_noise::<White>(app);
+        register_noise::<BlueNoisePass>(app);
+
+        app
+            .init_resource::<ComputeNoiseReadbackQueue>()
+            .init_resource::<NoiseTerrainDisplacements>()
+            .register_type::<NoiseTerrain>()
+            .add_systems(PostUpdate, (build_noise_terrain, finish_noise_terrain_readbacks));
+
+        #[cfg(feature = "export")]
+        app
+            .init_resource::<export::ComputeNoiseSaveQueue>()
+            .add_systems(PreUpdate, export::save_compute_noise_images)
+            .add_systems(PostUpdate, export::request_compute_noise_saves);
+
+        #[cfg(feature = "material")]
+        {
+            embedded_asset!(app, "material/shaders/bump.wgsl");
+            embedded_asset!(app, "material/shaders/noise_material.wgsl");
+            embedded_asset!(app, "material/shaders/triplanar_material.wgsl");
+            app
+                .init_resource::<material::MaterialShaderModules>()
+                .register_type::<material::NoiseMaterialExtension>()
+                .register_type::<material::TriplanarMaterialExtension>()
+                .add_plugins((
+                    MaterialPlugin::<material::NoiseMaterial>::default(),
+                    MaterialPlugin::<material::TriplanarMaterial>::default(),
+                ));
+        }
+
         let render_app = app.sub_app_mut(RenderApp);
 
         render_app
             .init_resource::<ComputeNoiseRenderQueue>()
-            .add_systems(ExtractSchedule, extract_compute_noise_queue)
+            .init_resource::<ComputeNoiseBindGroupCache>()
+            .init_resource::<ComputeNoiseDispatches>()
+            .init_resource::<ComputeNoiseWarmUp>()
+            .init_resource::<ComputeNoiseReadbacks>()
+            .init_resource::<PendingTerrainDisplacements>()
+            .insert_resource(expression_errors)
+            .add_systems(ExtractSchedule, (extract_compute_noise_queue, extract_compute_noise_warm_up, extract_compute_noise_readbacks, extract_terrain_displacements))
             .add_systems(
                 Render,
                 (
+                    (load_expression_shaders, warm_up_pipelines).chain().in_set(RenderSet::Prepare),
                     prepare_render_noise.in_set(RenderSet::PrepareBindGroups),
-                    (compute_noise, submit_compute_noise).after(RenderSet::PrepareBindGroups).before(RenderSet::Render).chain(),
+                    (prepare_compute_noise_dispatches, compute_noise, submit_compute_noise).after(RenderSet::PrepareBindGroups).before(RenderSet::Render).chain(),
+                    report_expression_errors.after(prepare_compute_noise_dispatches),
+                    prepare_compute_noise_readbacks.after(prepare_compute_noise_dispatches).before(compute_noise),
+                    map_compute_noise_readbacks.in_set(RenderSet::Cleanup),
+                    prepare_terrain_displacements.after(prepare_compute_noise_dispatches).before(compute_noise),
                 )
             );
+
+        let mut render_graph = render_app.world_mut().resource_mut::<RenderGraph>();
+        render_graph.add_node(ComputeNoiseLabel, ComputeNoiseNode);
+        render_graph.add_node_edge(ComputeNoiseLabel, CameraDriverLabel);
     }
 
     fn finish(&self, app: &mut App) {
+        app.init_resource::<ComputeNoiseBufferPool>();
+
         let render_app = app.sub_app_mut(RenderApp);
         render_app
             .init_resource::<ComputeNoisePipeline>()
             .init_resource::<SpecializedComputePipelines<ComputeNoisePipeline>>()
-            .init_resource::<ComputeNoiseEncoder>();
+            .init_resource::<ComputeNoiseEncoder>()
+            .init_resource::<TerrainDisplacementPipeline>();
+        load_cloud_shaders(render_app.world_mut());
+        load_white_shaders(render_app.world_mut());
+        load_blue_noise_shaders(render_app.world_mut());
     }
 }
\ No newline at end of file
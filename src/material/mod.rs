@@ -0,0 +1,163 @@
+use bevy::{
+    pbr::{ExtendedMaterial, MaterialExtension},
+    prelude::*,
+    render::{render_asset::RenderAssets, render_resource::{AsBindGroup, AsBindGroupShaderType, ShaderRef}, texture::GpuImage},
+};
+use bytemuck::{Pod, Zeroable};
+use serde::{Deserialize, Serialize};
+
+use crate::noise::generators::{ComputeNoiseGenerator, Fbm, Perlin, Worley};
+
+pub mod triplanar;
+
+pub use triplanar::{TriplanarMaterial, TriplanarMaterialExtension};
+
+/// Modules imported by the material shaders, kept loaded so their import paths resolve.
+#[derive(Resource)]
+pub(crate) struct MaterialShaderModules {
+    _modules: Vec<Handle<Shader>>,
+}
+
+impl FromWorld for MaterialShaderModules {
+    fn from_world(world: &mut World) -> Self {
+        let asset_server = world.resource::<AssetServer>();
+        Self {
+            _modules: vec![asset_server.load("embedded://bevy_compute_noise/material/shaders/bump.wgsl")],
+        }
+    }
+}
+
+/// A [`StandardMaterial`] with noise sampled in its fragment shader instead of baked into a texture.
+pub type NoiseMaterial = ExtendedMaterial<StandardMaterial, NoiseMaterialExtension>;
+
+/// Noise a [`NoiseMaterialExtension`] samples, set `octaves` to 1 for a single layer.
+#[derive(Clone, Reflect, Serialize, Deserialize)]
+#[reflect(Default)]
+pub enum MaterialNoise {
+    Perlin(Fbm<Perlin>),
+    Worley(Fbm<Worley>),
+}
+
+impl Default for MaterialNoise {
+    fn default() -> Self {
+        Self::Perlin(Fbm::default())
+    }
+}
+
+/// Perturbs a [`StandardMaterial`]'s base colour and normals with `noise`, see [`NoiseMaterial`].
+///
+/// The noise is sampled at the mesh's uvs, or at the world position with [`NoiseMaterialFlags::WORLD_SPACE`],
+/// where `frequency` is cells per world unit. Normals are bumped by the slope of the noise on screen, so meshes
+/// don't need tangents, but the bumps aren't in the normal prepass.
+///
+/// ```ignore
+/// materials.add(NoiseMaterial {
+///     base: StandardMaterial::default(),
+///     extension: NoiseMaterialExtension { color_strength: 0.5, normal_strength: 0.2, ..default() },
+/// });
+/// ```
+#[derive(Asset, AsBindGroup, Clone, Reflect)]
+#[reflect(Default)]
+#[uniform(100, NoiseMaterialUniform)]
+pub struct NoiseMaterialExtension {
+    pub noise: MaterialNoise,
+    /// Blended over the base colour by the noise's value.
+    pub color: LinearRgba,
+    /// How much of `color` there is where the noise is 1.0, 0.0 leaves the base colour alone.
+    pub color_strength: f32,
+    /// Height of the bumps, 0.0 leaves the normals alone.
+    pub normal_strength: f32,
+    pub flags: NoiseMaterialFlags,
+}
+
+#[derive(Clone, Copy, Reflect, PartialEq, Eq, Hash, Debug, Default, Pod, Zeroable, Serialize, Deserialize)]
+#[reflect(Default, Debug, PartialEq, Hash, Serialize, Deserialize)]
+#[serde(transparent)]
+#[repr(transparent)]
+pub struct NoiseMaterialFlags(u32);
+
+bitflags::bitflags! {
+    impl NoiseMaterialFlags: u32 {
+        const WORLD_SPACE = 1 << 0;
+    }
+}
+
+impl Default for NoiseMaterialExtension {
+    fn default() -> Self {
+        Self {
+            noise: MaterialNoise::default(),
+            color: LinearRgba::BLACK,
+            color_strength: 0.0,
+            normal_strength: 0.0,
+            flags: NoiseMaterialFlags::empty(),
+        }
+    }
+}
+
+pub use uniform::NoiseMaterialUniform;
+
+// The checks `ShaderType` derives for each field are reported as dead code.
+#[allow(dead_code)]
+mod uniform {
+    use bevy::{math::Vec4, render::render_resource::ShaderType};
+
+    /// Matches `NoiseMaterial` in `noise_material.wgsl`.
+    #[derive(Clone, Default, ShaderType)]
+    pub struct NoiseMaterialUniform {
+        pub color: Vec4,
+        /// 0 for perlin, 1 for worley.
+        pub noise: u32,
+        pub seed: u32,
+        pub frequency: f32,
+        pub noise_flags: u32,
+        pub octaves: u32,
+        pub lacunarity: f32,
+        pub persistence: f32,
+        pub fbm_flags: u32,
+        pub color_strength: f32,
+        pub normal_strength: f32,
+        pub flags: u32,
+    }
+}
+
+impl AsBindGroupShaderType<NoiseMaterialUniform> for NoiseMaterialExtension {
+    fn as_bind_group_shader_type(&self, _images: &RenderAssets<GpuImage>) -> NoiseMaterialUniform {
+        let (noise, seed, frequency, noise_flags) = match &self.noise {
+            MaterialNoise::Perlin(fbm) => (0, fbm.noise.seed, fbm.noise.frequency, fbm.noise.flags.bits()),
+            MaterialNoise::Worley(fbm) => (1, fbm.noise.seed, fbm.noise.frequency, fbm.noise.flags.bits()),
+        };
+        let (octaves, lacunarity, persistence, fbm_flags) = match &self.noise {
+            MaterialNoise::Perlin(fbm) => fbm_settings(fbm),
+            MaterialNoise::Worley(fbm) => fbm_settings(fbm),
+        };
+
+        NoiseMaterialUniform {
+            color: self.color.to_vec4(),
+            noise,
+            seed,
+            frequency,
+            noise_flags,
+            octaves,
+            lacunarity,
+            persistence,
+            fbm_flags,
+            color_strength: self.color_strength,
+            normal_strength: self.normal_strength,
+            flags: self.flags.bits(),
+        }
+    }
+}
+
+fn fbm_settings<T: ComputeNoiseGenerator>(fbm: &Fbm<T>) -> (u32, f32, f32, u32) {
+    (fbm.octaves, fbm.lacunarity, fbm.persistence, fbm.flags.bits())
+}
+
+impl MaterialExtension for NoiseMaterialExtension {
+    fn fragment_shader() -> ShaderRef {
+        "embedded://bevy_compute_noise/material/shaders/noise_material.wgsl".into()
+    }
+
+    fn deferred_fragment_shader() -> ShaderRef {
+        "embedded://bevy_compute_noise/material/shaders/noise_material.wgsl".into()
+    }
+}
@@ -0,0 +1,13 @@
+#define_import_path bevy_compute_noise::bump
+
+// Bumps `normal` by the slope of `height` across the surface, using screen space derivatives
+// so it works without tangents. See Mikkelsen, "Bump Mapping Unparametrized Surfaces on the GPU".
+fn bump_normal(normal: vec3<f32>, position: vec3<f32>, height: f32) -> vec3<f32> {
+    let dp_dx = dpdx(position);
+    let dp_dy = dpdy(position);
+    let r1 = cross(dp_dy, normal);
+    let r2 = cross(normal, dp_dx);
+    let det = dot(dp_dx, r1);
+    let surface_gradient = sign(det) * (dpdx(height) * r1 + dpdy(height) * r2);
+    return normalize(abs(det) * normal - surface_gradient);
+}
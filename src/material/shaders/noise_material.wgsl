@@ -0,0 +1,91 @@
+// Fragment shader of `NoiseMaterial`, a `StandardMaterial` with its base colour and normals perturbed by noise.
+
+#import bevy_pbr::{
+    pbr_fragment::pbr_input_from_standard_material,
+    pbr_functions::alpha_discard,
+}
+#ifdef PREPASS_PIPELINE
+#import bevy_pbr::{
+    prepass_io::{VertexOutput, FragmentOutput},
+    pbr_deferred_functions::deferred_output,
+}
+#else
+#import bevy_pbr::{
+    forward_io::{VertexOutput, FragmentOutput},
+    pbr_functions::{apply_pbr_lighting, main_pass_post_lighting_processing},
+}
+#endif
+#import bevy_compute_noise::perlin::Perlin
+#import bevy_compute_noise::worley::Worley
+#import bevy_compute_noise::fbm::{Fbm, fbm_perlin_2d, fbm_perlin_3d, fbm_worley_2d, fbm_worley_3d}
+#import bevy_compute_noise::bump::bump_normal
+
+const WORLEY: u32 = 1u;
+const WORLD_SPACE: u32 = 1u;
+
+struct NoiseMaterial {
+    color: vec4<f32>,
+    noise: u32,
+    seed: u32,
+    frequency: f32,
+    noise_flags: u32,
+    octaves: u32,
+    lacunarity: f32,
+    persistence: f32,
+    fbm_flags: u32,
+    color_strength: f32,
+    normal_strength: f32,
+    flags: u32,
+}
+@group(2) @binding(100) var<uniform> noise_material: NoiseMaterial;
+
+fn sample_noise(in: VertexOutput) -> f32 {
+    let fbm = Fbm(noise_material.octaves, noise_material.lacunarity, noise_material.persistence, noise_material.fbm_flags);
+    let perlin = Perlin(noise_material.seed, noise_material.frequency, noise_material.noise_flags);
+    let worley = Worley(noise_material.seed, noise_material.frequency, noise_material.noise_flags);
+
+    if (noise_material.flags & WORLD_SPACE) != 0u {
+        let p = in.world_position.xyz;
+        if noise_material.noise == WORLEY { return fbm_worley_3d(p, worley, fbm); }
+        return fbm_perlin_3d(p, perlin, fbm);
+    }
+
+#ifdef VERTEX_UVS_A
+    let uv = in.uv;
+#else
+    let uv = vec2(0.0);
+#endif
+    if noise_material.noise == WORLEY { return fbm_worley_2d(uv, worley, fbm); }
+    return fbm_perlin_2d(uv, perlin, fbm);
+}
+
+@fragment
+fn fragment(
+    in: VertexOutput,
+    @builtin(front_facing) is_front: bool,
+) -> FragmentOutput {
+    var pbr_input = pbr_input_from_standard_material(in, is_front);
+
+    let value = sample_noise(in);
+    let base_color = pbr_input.material.base_color;
+    let blend = saturate(value * noise_material.color_strength);
+    pbr_input.material.base_color = vec4(mix(base_color.rgb, noise_material.color.rgb, blend), base_color.a);
+
+    // Derivatives have to be taken outside of branches.
+    let bumped = bump_normal(pbr_input.N, in.world_position.xyz, value * noise_material.normal_strength);
+    if noise_material.normal_strength != 0.0 {
+        pbr_input.N = bumped;
+    }
+
+    pbr_input.material.base_color = alpha_discard(pbr_input.material, pbr_input.material.base_color);
+
+#ifdef PREPASS_PIPELINE
+    let out = deferred_output(in, pbr_input);
+#else
+    var out: FragmentOutput;
+    out.color = apply_pbr_lighting(pbr_input);
+    out.color = main_pass_post_lighting_processing(pbr_input, out.color);
+#endif
+
+    return out;
+}
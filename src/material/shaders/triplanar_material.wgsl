@@ -0,0 +1,70 @@
+// Fragment shader of `TriplanarMaterial`, a `StandardMaterial` with its base colour and normals perturbed
+// by the red channel of a 3D noise texture, sampled in world space.
+
+#import bevy_pbr::{
+    pbr_fragment::pbr_input_from_standard_material,
+    pbr_functions::alpha_discard,
+}
+#ifdef PREPASS_PIPELINE
+#import bevy_pbr::{
+    prepass_io::{VertexOutput, FragmentOutput},
+    pbr_deferred_functions::deferred_output,
+}
+#else
+#import bevy_pbr::{
+    forward_io::{VertexOutput, FragmentOutput},
+    pbr_functions::{apply_pbr_lighting, main_pass_post_lighting_processing},
+}
+#endif
+#import bevy_compute_noise::triplanar::sample_triplanar
+#import bevy_compute_noise::bump::bump_normal
+
+struct TriplanarMaterial {
+    color: vec4<f32>,
+    scale: f32,
+    sharpness: f32,
+    color_strength: f32,
+    normal_strength: f32,
+}
+@group(2) @binding(100) var<uniform> triplanar_material: TriplanarMaterial;
+@group(2) @binding(101) var volume: texture_3d<f32>;
+@group(2) @binding(102) var volume_sampler: sampler;
+
+@fragment
+fn fragment(
+    in: VertexOutput,
+    @builtin(front_facing) is_front: bool,
+) -> FragmentOutput {
+    var pbr_input = pbr_input_from_standard_material(in, is_front);
+
+    // Blended by the surface's normal, not the bumped one.
+    let value = sample_triplanar(
+        volume,
+        volume_sampler,
+        in.world_position.xyz,
+        normalize(in.world_normal),
+        triplanar_material.scale,
+        triplanar_material.sharpness,
+    ).r;
+    let base_color = pbr_input.material.base_color;
+    let blend = saturate(value * triplanar_material.color_strength);
+    pbr_input.material.base_color = vec4(mix(base_color.rgb, triplanar_material.color.rgb, blend), base_color.a);
+
+    // Derivatives have to be taken outside of branches.
+    let bumped = bump_normal(pbr_input.N, in.world_position.xyz, value * triplanar_material.normal_strength);
+    if triplanar_material.normal_strength != 0.0 {
+        pbr_input.N = bumped;
+    }
+
+    pbr_input.material.base_color = alpha_discard(pbr_input.material, pbr_input.material.base_color);
+
+#ifdef PREPASS_PIPELINE
+    let out = deferred_output(in, pbr_input);
+#else
+    var out: FragmentOutput;
+    out.color = apply_pbr_lighting(pbr_input);
+    out.color = main_pass_post_lighting_processing(pbr_input, out.color);
+#endif
+
+    return out;
+}
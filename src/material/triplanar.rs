@@ -0,0 +1,93 @@
+use bevy::{
+    pbr::{ExtendedMaterial, MaterialExtension},
+    prelude::*,
+    render::{render_asset::RenderAssets, render_resource::{AsBindGroup, AsBindGroupShaderType, ShaderRef}, texture::GpuImage},
+};
+
+/// A [`StandardMaterial`] with a 3D noise texture projected onto it in world space.
+pub type TriplanarMaterial = ExtendedMaterial<StandardMaterial, TriplanarMaterialExtension>;
+
+/// Perturbs a [`StandardMaterial`]'s base colour and normals with the red channel of `volume`, see [`TriplanarMaterial`].
+///
+/// The volume is sampled along each world axis and the samples blended by the surface normal, so meshes don't
+/// need uvs and there are no seams between faces. `volume` should tile and sample with repeat addressing, like
+/// images from [`ComputeNoiseImage::create_mipmapped_image`](crate::image::ComputeNoiseImage::create_mipmapped_image)
+/// or [`CloudNoise`](crate::noise::generators::CloudNoise). The same projection is in the `bevy_compute_noise::triplanar`
+/// shader module for custom materials.
+///
+/// ```ignore
+/// materials.add(TriplanarMaterial {
+///     base: StandardMaterial::default(),
+///     extension: TriplanarMaterialExtension { volume: clouds.shape, color_strength: 1.0, ..default() },
+/// });
+/// ```
+#[derive(Asset, AsBindGroup, Clone, Reflect)]
+#[reflect(Default)]
+#[uniform(100, TriplanarMaterialUniform)]
+pub struct TriplanarMaterialExtension {
+    #[texture(101, dimension = "3d")]
+    #[sampler(102)]
+    pub volume: Handle<Image>,
+    /// Times the volume repeats per world unit.
+    pub scale: f32,
+    /// Higher values narrow the blend between projections where the surface isn't facing an axis.
+    pub sharpness: f32,
+    /// Blended over the base colour by the volume's value.
+    pub color: LinearRgba,
+    /// How much of `color` there is where the volume is 1.0, 0.0 leaves the base colour alone.
+    pub color_strength: f32,
+    /// Height of the bumps, 0.0 leaves the normals alone.
+    pub normal_strength: f32,
+}
+
+impl Default for TriplanarMaterialExtension {
+    fn default() -> Self {
+        Self {
+            volume: Handle::default(),
+            scale: 0.25,
+            sharpness: 4.0,
+            color: LinearRgba::BLACK,
+            color_strength: 0.0,
+            normal_strength: 0.0,
+        }
+    }
+}
+
+pub use uniform::TriplanarMaterialUniform;
+
+#[allow(dead_code)]
+mod uniform {
+    use bevy::{math::Vec4, render::render_resource::ShaderType};
+
+    /// Matches `TriplanarMaterial` in `triplanar_material.wgsl`.
+    #[derive(Clone, Default, ShaderType)]
+    pub struct TriplanarMaterialUniform {
+        pub color: Vec4,
+        pub scale: f32,
+        pub sharpness: f32,
+        pub color_strength: f32,
+        pub normal_strength: f32,
+    }
+}
+
+impl AsBindGroupShaderType<TriplanarMaterialUniform> for TriplanarMaterialExtension {
+    fn as_bind_group_shader_type(&self, _images: &RenderAssets<GpuImage>) -> TriplanarMaterialUniform {
+        TriplanarMaterialUniform {
+            color: self.color.to_vec4(),
+            scale: self.scale,
+            sharpness: self.sharpness,
+            color_strength: self.color_strength,
+            normal_strength: self.normal_strength,
+        }
+    }
+}
+
+impl MaterialExtension for TriplanarMaterialExtension {
+    fn fragment_shader() -> ShaderRef {
+        "embedded://bevy_compute_noise/material/shaders/triplanar_material.wgsl".into()
+    }
+
+    fn deferred_fragment_shader() -> ShaderRef {
+        "embedded://bevy_compute_noise/material/shaders/triplanar_material.wgsl".into()
+    }
+}
@@ -0,0 +1,209 @@
+use bevy::{prelude::*, render::render_resource::Buffer};
+use bytemuck::{Pod, Zeroable};
+use serde::{Deserialize, Serialize};
+
+use crate::{buffer_pool::ComputeNoiseBufferPool, image::ComputeNoiseSize, noise::modifiers::PingPongBuffers, noise_queue::{ComputeNoiseSequence, IntoNoiseSequence}, render::pipeline::NoiseOp, shader::ComputeNoiseShader};
+
+use super::ComputeNoise;
+
+/// Tileable blue noise, random values per texel with neighbours that differ as much as possible,
+/// for dithering and sampling without the clumps white noise has. 2D and 2D array textures only.
+///
+/// Starts from white noise and relaxes it on the GPU, each iteration swaps the values of pairs of texels
+/// wherever that lowers the energy from "Blue-noise Dithered Sampling" by Georgiev and Fajardo.
+/// Every channel is relaxed on its own and ranked at the end with a bitonic sort, so each is uniformly distributed.
+/// Layers of up to 8192x8192 texels are sorted, wgpu's default size limit.
+///
+/// With [`BlueNoiseFlags::SPATIOTEMPORAL`], the layers of an array texture are treated as frames,
+/// so the values of each texel over time are blue noise too, for dithering that resolves under TAA.
+/// Otherwise every layer is its own texture with its own seed.
+#[derive(Clone, Copy, Reflect, PartialEq, Debug, Serialize, Deserialize)]
+#[reflect(Default, Serialize, Deserialize)]
+pub struct BlueNoise {
+    pub seed: u32,
+    pub iterations: u32,
+    /// Spread the relaxation and the sort over multiple frames, running this many passes of each per frame.
+    /// 0 runs every pass in one frame.
+    pub iterations_per_frame: u32,
+    /// Distance in texels, and frames, over which similar values push each other apart.
+    pub sigma: f32,
+    pub flags: BlueNoiseFlags,
+}
+
+#[derive(Clone, Copy, Reflect, PartialEq, Eq, Hash, Debug, Default, Pod, Zeroable, Serialize, Deserialize)]
+#[reflect(Default, Debug, PartialEq, Hash, Serialize, Deserialize)]
+#[serde(transparent)]
+#[repr(transparent)]
+pub struct BlueNoiseFlags(u32);
+
+bitflags::bitflags! {
+    impl BlueNoiseFlags: u32 {
+        const SPATIOTEMPORAL = 1 << 0;
+    }
+}
+
+impl Default for BlueNoise {
+    fn default() -> Self {
+        Self {
+            seed: 0,
+            iterations: 256,
+            iterations_per_frame: 0,
+            sigma: 2.1,
+            flags: BlueNoiseFlags::empty(),
+        }
+    }
+}
+
+impl IntoNoiseSequence for BlueNoise {
+    fn into_sequence(self, output: Handle<Image>) -> ComputeNoiseSequence {
+        let values = PingPongBuffers::default();
+        let pass = |stage: BlueNoiseStage, iteration: u32, source: usize| BlueNoisePass {
+            uniform: BlueNoiseUniform {
+                stage: stage as u32,
+                seed: self.seed,
+                iteration,
+                flags: self.flags.bits(),
+                sigma: self.sigma,
+                ..default()
+            },
+            source,
+            block: 0,
+            values: values.clone(),
+        };
+        let frame_break = |pass: u32| self.iterations_per_frame != 0 && (pass + 1).is_multiple_of(self.iterations_per_frame);
+
+        let mut sequence = pass(BlueNoiseStage::Seed, 0, 1).into_sequence(output.clone());
+        for i in 0..self.iterations {
+            let mut step = pass(BlueNoiseStage::Relax, i, i as usize % 2).into_sequence(output.clone());
+            step.0[0].frame_break = frame_break(i);
+            sequence.0.extend(step.0);
+        }
+
+        // The sort swaps values in place, in the buffer the last relaxation wrote to. The size of the texture
+        // isn't known yet, so there are blocks for the largest layer and smaller ones skip those they don't need.
+        let sorted = self.iterations as usize % 2;
+        let mut passes = 0;
+        for block in 1..=SORT_STAGES {
+            // The first pass of each block mirrors it, leaving two halves to sort in the same direction.
+            let masks = std::iter::once((1 << block) - 1).chain((0..block - 1).rev().map(|step| 1 << step));
+            for partner_mask in masks {
+                let mut sort = pass(BlueNoiseStage::Sort, 0, 1 - sorted);
+                sort.uniform.partner_mask = partner_mask;
+                sort.block = block;
+                let mut step = sort.into_sequence(output.clone());
+                step.0[0].frame_break = frame_break(passes);
+                sequence.0.extend(step.0);
+                passes += 1;
+            }
+        }
+        sequence.0.extend(pass(BlueNoiseStage::Rank, 0, sorted).into_sequence(output.clone()).0);
+        sequence.0.extend(pass(BlueNoiseStage::Store, 0, 1 - sorted).into_sequence(output).0);
+
+        sequence
+    }
+}
+
+/// Bitonic sort stages for up to 2^26 texels per layer.
+const SORT_STAGES: u32 = 26;
+
+/// Stages needed to sort a layer of `size`, as if it were padded to a power of two.
+fn sort_stages(size: ComputeNoiseSize) -> u32 {
+    (size.width() * size.height()).next_power_of_two().trailing_zeros()
+}
+
+#[derive(Clone, Copy)]
+enum BlueNoiseStage {
+    Seed,
+    Relax,
+    Sort,
+    Rank,
+    Store,
+}
+
+#[derive(Clone, Copy, Reflect, Default, Pod, Zeroable)]
+#[repr(C)]
+struct BlueNoiseUniform {
+    stage: u32,
+    seed: u32,
+    iteration: u32,
+    flags: u32,
+    sigma: f32,
+    layers: u32,
+    partner_mask: u32,
+    _padding: u32,
+}
+
+/// A single dispatch of the relaxation, sort or store, the texel values live in [`PingPongBuffers`].
+#[derive(Clone, Reflect, Default)]
+pub(crate) struct BlueNoisePass {
+    uniform: BlueNoiseUniform,
+    source: usize,
+    /// Bitonic block of sort passes, the ones a layer is too small for are skipped.
+    block: u32,
+    #[reflect(ignore)]
+    values: PingPongBuffers,
+}
+
+impl ComputeNoise for BlueNoisePass {
+    const NOISE_OP: NoiseOp = NoiseOp::Generator;
+    const SUPPORTS_REGION: bool = false;
+
+    fn buffers(&self, pool: &mut ComputeNoiseBufferPool, size: ComputeNoiseSize) -> Vec<Buffer> {
+        let [source, target] = self.values.get(
+            pool.render_device(),
+            "blue_noise_value_buffer",
+            (size.data_len() * std::mem::size_of::<[[u32; 4]; 2]>()) as u64,
+            self.source,
+        );
+        let uniform = BlueNoiseUniform { layers: size.layers(), ..self.uniform };
+
+        vec![
+            pool.uniform("blue_noise_buffer", bytemuck::cast_slice(&[uniform])),
+            source,
+            target,
+        ]
+    }
+
+    fn runs_on(&self, size: ComputeNoiseSize) -> bool {
+        self.block <= sort_stages(size)
+    }
+}
+
+impl ComputeNoiseShader for BlueNoisePass {
+    fn function_name() -> &'static str {
+        "blue_noise"
+    }
+
+    fn import_path() -> &'static str {
+        "bevy_compute_noise::blue_noise"
+    }
+
+    fn struct_name() -> Option<&'static str> {
+        Some("BlueNoise")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sequence_relaxes_sorts_and_ranks() {
+        let sequence = BlueNoise { iterations: 5, iterations_per_frame: 2, ..default() }.into_sequence(Handle::default());
+        let passes = sequence.passes(|pass: &BlueNoisePass| (pass.uniform.stage, pass.uniform.partner_mask, pass.source));
+
+        // The relaxation ends in the second buffer, which the sort swaps in place.
+        let sort_passes = (SORT_STAGES * (SORT_STAGES + 1) / 2) as usize;
+        assert_eq!(passes.len(), 1 + 5 + sort_passes + 2);
+        assert_eq!(passes[..6], [(0, 0, 1), (1, 0, 0), (1, 0, 1), (1, 0, 0), (1, 0, 1), (1, 0, 0)]);
+        assert_eq!(passes[6..12], [(2, 1, 0), (2, 3, 0), (2, 1, 0), (2, 7, 0), (2, 2, 0), (2, 1, 0)]);
+        assert_eq!(passes[passes.len() - 3..], [(2, 1, 0), (3, 0, 1), (4, 0, 0)]);
+
+        assert_eq!(sequence.frame_breaks()[..10], [false, false, true, false, true, false, false, true, false, true]);
+
+        // Only the blocks that fit in a layer are sorted, 2^12 texels and 2^11 once padded.
+        let runs_on = |size| sequence.0.iter().filter(|instruction| instruction.noise.runs_on(size)).count();
+        assert_eq!(runs_on(ComputeNoiseSize::D2(64, 64)), 1 + 5 + 78 + 2);
+        assert_eq!(runs_on(ComputeNoiseSize::D2Array(48, 40, 4)), 1 + 5 + 66 + 2);
+    }
+}
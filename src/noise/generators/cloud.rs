@@ -0,0 +1,169 @@
+use bevy::{prelude::*, render::render_resource::Buffer};
+use bytemuck::{Pod, Zeroable};
+use serde::{Deserialize, Serialize};
+
+use crate::{buffer_pool::ComputeNoiseBufferPool, error::ComputeNoiseError, image::{ComputeNoiseImage, ComputeNoiseSize}, noise_queue::ComputeNoiseQueue, render::pipeline::NoiseOp, shader::ComputeNoiseShader};
+
+use super::ComputeNoise;
+
+/// Low frequency shape texture for volumetric clouds, 3D textures only.
+///
+/// Red is perlin-worley, fbm perlin noise remapped by worley fbm so it breaks up into billowy cells.
+/// Green, blue and alpha are worley fbm at `worley_frequency`, double and quadruple it, for eroding the shape.
+/// Tiles in every axis, so the frequencies are rounded down to whole cells, at least one.
+#[derive(Clone, Copy, Reflect, PartialEq, Debug, Pod, Zeroable, Serialize, Deserialize)]
+#[reflect(Default, Serialize, Deserialize)]
+#[repr(C)]
+pub struct CloudShape {
+    pub seed: u32,
+    pub perlin_frequency: f32,
+    pub perlin_octaves: u32,
+    pub worley_frequency: f32,
+}
+
+impl Default for CloudShape {
+    fn default() -> Self {
+        Self {
+            seed: 0,
+            perlin_frequency: 4.0,
+            perlin_octaves: 5,
+            worley_frequency: 4.0,
+        }
+    }
+}
+
+impl ComputeNoise for CloudShape {
+    const NOISE_OP: NoiseOp = NoiseOp::Generator;
+
+    fn buffers(&self, pool: &mut ComputeNoiseBufferPool, _size: ComputeNoiseSize) -> Vec<Buffer> {
+        vec![
+            pool.uniform("cloud_shape_buffer", bytemuck::cast_slice(&[*self])),
+        ]
+    }
+}
+
+impl ComputeNoiseShader for CloudShape {
+    fn function_name() -> &'static str {
+        "cloud_shape"
+    }
+
+    fn import_path() -> &'static str {
+        "bevy_compute_noise::cloud"
+    }
+
+    fn struct_name() -> Option<&'static str> {
+        Some("Config")
+    }
+}
+
+/// High frequency detail texture for volumetric clouds, 3D textures only.
+///
+/// Red, green and blue are worley fbm at `frequency`, double and quadruple it, alpha is 1.
+/// Tiles in every axis, so the frequency is rounded down to whole cells, at least one.
+#[derive(Clone, Copy, Reflect, PartialEq, Debug, Pod, Zeroable, Serialize, Deserialize)]
+#[reflect(Default, Serialize, Deserialize)]
+#[repr(C)]
+pub struct CloudDetail {
+    pub seed: u32,
+    pub frequency: f32,
+}
+
+impl Default for CloudDetail {
+    fn default() -> Self {
+        Self {
+            seed: 0,
+            frequency: 4.0,
+        }
+    }
+}
+
+impl ComputeNoise for CloudDetail {
+    const NOISE_OP: NoiseOp = NoiseOp::Generator;
+
+    fn buffers(&self, pool: &mut ComputeNoiseBufferPool, _size: ComputeNoiseSize) -> Vec<Buffer> {
+        vec![
+            pool.uniform("cloud_detail_buffer", bytemuck::cast_slice(&[*self])),
+        ]
+    }
+}
+
+impl ComputeNoiseShader for CloudDetail {
+    fn function_name() -> &'static str {
+        "cloud_detail"
+    }
+
+    fn import_path() -> &'static str {
+        "bevy_compute_noise::cloud"
+    }
+
+    fn struct_name() -> Option<&'static str> {
+        Some("Config")
+    }
+}
+
+/// The pair of 3D textures volumetric cloud renderers sample, a [`CloudShape`] and a [`CloudDetail`] texture.
+///
+/// ```ignore
+/// let clouds = CloudNoise::default().queue(&mut images, &mut noise_queue).unwrap();
+/// ```
+#[derive(Clone, Copy, Reflect, PartialEq, Debug, Serialize, Deserialize)]
+#[reflect(Default, Serialize, Deserialize)]
+pub struct CloudNoise {
+    pub shape: CloudShape,
+    pub detail: CloudDetail,
+    /// Width, height and depth of the shape texture.
+    pub shape_size: u32,
+    /// Width, height and depth of the detail texture.
+    pub detail_size: u32,
+}
+
+impl Default for CloudNoise {
+    fn default() -> Self {
+        Self {
+            shape: CloudShape::default(),
+            detail: CloudDetail::default(),
+            shape_size: 128,
+            detail_size: 32,
+        }
+    }
+}
+
+/// Images created by [`CloudNoise::queue`].
+#[derive(Clone, Debug)]
+pub struct CloudNoiseImages {
+    pub shape: Handle<Image>,
+    pub detail: Handle<Image>,
+}
+
+impl CloudNoise {
+    /// Creates both textures, mipmapped and sampled with repeat addressing, and queues their noise.
+    pub fn queue(&self, images: &mut Assets<Image>, noise_queue: &mut ComputeNoiseQueue) -> Result<CloudNoiseImages, ComputeNoiseError> {
+        let image = |size| ComputeNoiseImage::create_mipmapped_image(ComputeNoiseSize::D3(size, size, size), true);
+        let clouds = CloudNoiseImages {
+            shape: images.add(image(self.shape_size)),
+            detail: images.add(image(self.detail_size)),
+        };
+
+        noise_queue.queue(clouds.shape.clone(), self.shape)?;
+        noise_queue.queue(clouds.detail.clone(), self.detail)?;
+        Ok(clouds)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn queues_both_textures() {
+        let mut images = Assets::<Image>::default();
+        let mut noise_queue = ComputeNoiseQueue::default();
+        noise_queue.register::<CloudShape>();
+        noise_queue.register::<CloudDetail>();
+
+        let clouds = CloudNoise::default().queue(&mut images, &mut noise_queue).unwrap();
+        assert_eq!(ComputeNoiseSize::from(images.get(&clouds.shape).unwrap()), ComputeNoiseSize::D3(128, 128, 128));
+        assert_eq!(ComputeNoiseSize::from(images.get(&clouds.detail).unwrap()), ComputeNoiseSize::D3(32, 32, 32));
+        assert_eq!(noise_queue.queue.len(), 2);
+    }
+}
@@ -0,0 +1,190 @@
+use std::{any::TypeId, hash::{DefaultHasher, Hash, Hasher}, sync::{Arc, Mutex}};
+
+use bevy::{prelude::*, render::{extract_resource::ExtractResource, render_resource::Buffer}, utils::HashMap};
+use serde::{Deserialize, Serialize};
+use crate::{buffer_pool::ComputeNoiseBufferPool, image::ComputeNoiseSize, noise_queue::ComputeNoiseQueue, render::{compute::ComputeNoiseDispatches, pipeline::NoiseOp}, shader::ComputeNoiseShader};
+
+use super::ComputeNoise;
+
+/// Noise from a WGSL expression, compiled into its own pipeline the first time each source is queued.
+///
+/// The expression evaluates to an `f32` and can use:
+/// - `uv`, the position in the texture from 0 to 1, a `vec2<f32>` or a `vec3<f32>` on 3D textures.
+/// - `p`, the position in 3D. That's `vec3(uv, 0.0)` on 2D and array textures, and the direction
+///   through the texel remapped to 0 to 1 on cube faces.
+/// - `layer`, the array layer or cube face.
+/// - `params[i]`, the values in `params`, which can change without recompiling.
+/// - `perlin(p, frequency)` and `worley(p, frequency)`, or `perlin_3d` and `worley_3d` with
+///   [`Perlin`](super::Perlin) and [`Worley`](super::Worley) settings.
+///
+/// ```ignore
+/// ExpressionNoise::new("perlin(p, params[0]) * (1.0 - worley(p, 8.0))", vec![4.0])
+/// ```
+///
+/// Expressions that fail to compile are sent as [`ExpressionNoiseError`] events and nothing is written.
+#[derive(Clone, Default, Reflect, Serialize, Deserialize)]
+#[reflect(Default, Serialize, Deserialize)]
+pub struct ExpressionNoise {
+    pub source: String,
+    pub params: Vec<f32>,
+}
+
+impl ExpressionNoise {
+    pub fn new(source: impl Into<String>, params: Vec<f32>) -> Self {
+        Self { source: source.into(), params }
+    }
+}
+
+impl ComputeNoise for ExpressionNoise {
+    const NOISE_OP: NoiseOp = NoiseOp::Generator;
+
+    fn buffers(&self, pool: &mut ComputeNoiseBufferPool, _size: ComputeNoiseSize) -> Vec<Buffer> {
+        // Storage buffers can't be empty.
+        let params: &[f32] = if self.params.is_empty() { &[0.0] } else { &self.params };
+        vec![
+            pool.storage("expression_params_buffer", bytemuck::cast_slice(params)),
+        ]
+    }
+
+    fn pipeline_variant(&self) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        self.source.hash(&mut hasher);
+        hasher.finish()
+    }
+}
+
+impl ComputeNoiseShader for ExpressionNoise {
+    fn function_name() -> &'static str {
+        "expression"
+    }
+
+    fn import_path() -> &'static str {
+        "bevy_compute_noise::expression"
+    }
+
+    fn struct_name() -> Option<&'static str> {
+        None
+    }
+}
+
+/// An [`ExpressionNoise`] failed to compile.
+#[derive(Event, Clone, Debug)]
+pub struct ExpressionNoiseError {
+    pub source: String,
+    pub message: String,
+}
+
+/// Shaders of every expression queued recently, by [`ComputeNoise::pipeline_variant`].
+#[derive(Resource, Clone, Default, ExtractResource)]
+pub(crate) struct ExpressionShaders {
+    pub shaders: HashMap<u64, (Handle<Shader>, String)>,
+}
+
+/// Expression shaders are removed after going unused for this many frames, longer than pooled buffers
+/// are kept so an expression isn't removed while its pipeline is still compiling.
+const MAX_UNUSED_FRAMES: u32 = 600;
+
+/// Each source always gets the same shader id, so the pipelines made for it before it was removed
+/// are compiled again when it's queued again.
+fn expression_shader_handle(variant: u64) -> Handle<Shader> {
+    Handle::weak_from_u128(0x6578_7072_6573_7369_u128 << 64 | variant as u128)
+}
+
+/// Compile errors reported by the render world, sent as events in the main world.
+#[derive(Resource, Clone, Default)]
+pub(crate) struct ExpressionErrors(Arc<Mutex<Vec<ExpressionNoiseError>>>);
+
+fn expression_shader(source: &str) -> Shader {
+    Shader::from_wgsl(
+        include_str!("shaders/expression.wgsl").replace("{{EXPRESSION}}", source),
+        format!("bevy_compute_noise/noise/generators/shaders/expression.wgsl ({source})"),
+    )
+}
+
+pub(crate) fn prepare_expression_shaders(
+    noise_queue: Res<ComputeNoiseQueue>,
+    mut expression_shaders: ResMut<ExpressionShaders>,
+    mut shaders: ResMut<Assets<Shader>>,
+    mut unused_frames: Local<HashMap<u64, u32>>,
+) {
+    unused_frames.retain(|variant, unused_frames| {
+        *unused_frames += 1;
+        if *unused_frames <= MAX_UNUSED_FRAMES { return true; }
+        if let Some((shader, _)) = expression_shaders.shaders.remove(variant) {
+            shaders.remove(&shader);
+        }
+        false
+    });
+
+    let expressions = noise_queue.queue.iter()
+        .flat_map(|sequence| sequence.0.iter())
+        .filter_map(|instruction| instruction.noise.as_noise::<ExpressionNoise>().map(|noise| (instruction.noise.variant, noise)));
+
+    for (variant, expression) in expressions {
+        unused_frames.insert(variant, 0);
+        if expression_shaders.shaders.contains_key(&variant) { continue; }
+        let shader = expression_shader_handle(variant);
+        shaders.insert(&shader, expression_shader(&expression.source));
+        expression_shaders.shaders.insert(variant, (shader, expression.source.clone()));
+    }
+}
+
+pub(crate) fn report_expression_errors(
+    dispatches: Res<ComputeNoiseDispatches>,
+    expression_shaders: Res<ExpressionShaders>,
+    errors: Res<ExpressionErrors>,
+) {
+    let failed = dispatches.failed.iter()
+        .filter(|(key, _)| key.type_id == TypeId::of::<ExpressionNoise>())
+        .filter_map(|(key, message)| Some(ExpressionNoiseError {
+            source: expression_shaders.shaders.get(&key.variant)?.1.clone(),
+            message: message.clone(),
+        }));
+    errors.0.lock().unwrap().extend(failed);
+}
+
+pub(crate) fn send_expression_errors(errors: Res<ExpressionErrors>, mut events: EventWriter<ExpressionNoiseError>) {
+    events.send_batch(errors.0.lock().unwrap().drain(..));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn variant_follows_source() {
+        let noise = |source: &str, params| ExpressionNoise::new(source, params);
+        assert_eq!(noise("perlin(p, 4.0)", vec![]).pipeline_variant(), noise("perlin(p, 4.0)", vec![1.0]).pipeline_variant());
+        assert_ne!(noise("perlin(p, 4.0)", vec![]).pipeline_variant(), noise("worley(p, 4.0)", vec![]).pipeline_variant());
+        assert!(expression_shader("uv.x").source.as_str().contains("return uv.x;"));
+    }
+
+    #[test]
+    fn unused_shaders_are_removed() {
+        let mut world = World::new();
+        world.init_resource::<ComputeNoiseQueue>();
+        world.init_resource::<ExpressionShaders>();
+        world.init_resource::<Assets<Shader>>();
+        world.resource_mut::<ComputeNoiseQueue>().register::<ExpressionNoise>();
+
+        let mut schedule = Schedule::default();
+        schedule.add_systems(prepare_expression_shaders);
+
+        let noise = ExpressionNoise::new("uv.x", vec![]);
+        let variant = noise.pipeline_variant();
+        world.resource_mut::<ComputeNoiseQueue>().queue(Handle::default(), noise).unwrap();
+        schedule.run(&mut world);
+        let shader = expression_shader_handle(variant);
+        assert!(world.resource::<Assets<Shader>>().contains(&shader));
+
+        world.resource_mut::<ComputeNoiseQueue>().queue.clear();
+        for _ in 0..MAX_UNUSED_FRAMES {
+            schedule.run(&mut world);
+        }
+        assert!(world.resource::<Assets<Shader>>().contains(&shader));
+
+        schedule.run(&mut world);
+        assert!(!world.resource::<Assets<Shader>>().contains(&shader));
+        assert!(world.resource::<ExpressionShaders>().shaders.is_empty());
+    }
+}
@@ -1,20 +1,28 @@
-use bevy::{reflect::Reflect, render::{render_resource::{Buffer, BufferInitDescriptor, BufferUsages}, renderer::RenderDevice}};
-use crate::{render::pipeline::NoiseOp, shader::ComputeNoiseShader};
+use bevy::{prelude::*, render::render_resource::Buffer};
+use bytemuck::{Pod, Zeroable};
+use serde::{Deserialize, Serialize};
+use crate::{buffer_pool::ComputeNoiseBufferPool, image::ComputeNoiseSize, render::pipeline::NoiseOp, shader::ComputeNoiseShader};
 
 use super::{ComputeNoise, ComputeNoiseGenerator};
 
-#[derive(Clone, Reflect)]
+#[derive(Clone, Reflect, Serialize, Deserialize)]
+#[reflect(Default)]
 pub struct Fbm<T: ComputeNoiseGenerator> {
     pub noise: T,
     pub octaves: u32,
     pub lacunarity: f32,
     pub persistence: f32,
-    pub flags: u32,
+    pub flags: FbmFlags,
 }
 
+#[derive(Clone, Copy, Reflect, PartialEq, Eq, Hash, Debug, Default, Pod, Zeroable, Serialize, Deserialize)]
+#[reflect(Default, Debug, PartialEq, Hash, Serialize, Deserialize)]
+#[serde(transparent)]
+#[repr(transparent)]
+pub struct FbmFlags(u32);
+
 bitflags::bitflags! {
-    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
-    pub struct FbmFlags: u32 {
+    impl FbmFlags: u32 {
         const INVERT = 1 << 0;
         const BILLOWY = 1 << 1;
     }
@@ -23,21 +31,15 @@ bitflags::bitflags! {
 impl<T: ComputeNoiseGenerator> ComputeNoise for Fbm<T> {
     const NOISE_OP: NoiseOp = NoiseOp::Generator; 
 
-    fn buffers(&self, render_device: &RenderDevice) -> Vec<Buffer> {
+    fn buffers(&self, pool: &mut ComputeNoiseBufferPool, _size: ComputeNoiseSize) -> Vec<Buffer> {
         vec![
-            render_device.create_buffer_with_data(
-                &BufferInitDescriptor {
-                    label: Some("combined_fbm_noise_buffer"),
-                    contents: &[bytemuck::cast_slice(&[
-                        self.octaves,
-                        self.lacunarity.to_bits(),
-                        self.persistence.to_bits(),
-                        self.flags,
-                    ]),
-                    bytemuck::cast_slice(&[self.noise.clone()])].concat(),
-                    usage: BufferUsages::UNIFORM | BufferUsages::COPY_DST
-                }
-            )
+            pool.uniform("combined_fbm_noise_buffer", &[bytemuck::cast_slice(&[
+                self.octaves,
+                self.lacunarity.to_bits(),
+                self.persistence.to_bits(),
+                self.flags.bits(),
+            ]),
+            bytemuck::cast_slice(&[self.noise])].concat()),
         ]
     }
 }
@@ -49,7 +51,7 @@ impl<T: ComputeNoiseGenerator> Default for Fbm<T> {
             octaves: 4,
             lacunarity: 2.0,
             persistence: 0.5,
-            flags: 0,
+            flags: FbmFlags::empty(),
         }
     }
 }
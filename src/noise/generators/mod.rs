@@ -1,19 +1,86 @@
-use bevy::{prelude::*, render::render_resource::{ShaderDefVal, ShaderRef}};
+use bevy::{prelude::*, render::render_resource::ShaderRef};
 
 pub mod worley;
 pub mod perlin;
 pub mod fbm;
+pub mod spherical;
+pub mod expression;
+pub mod cloud;
+pub mod white;
+pub mod blue_noise;
 
 use bytemuck::Pod;
 pub use worley::{Worley, WorleyFlags};
 pub use perlin::{Perlin, PerlinFlags};
-pub use fbm::Fbm;
+pub use fbm::{Fbm, FbmFlags};
+pub use spherical::Spherical;
+pub use expression::{ExpressionNoise, ExpressionNoiseError};
+pub use cloud::{CloudNoise, CloudNoiseImages, CloudShape, CloudDetail};
+pub use white::White;
+pub use blue_noise::{BlueNoise, BlueNoiseFlags};
+
+use crate::error::ComputeNoiseError;
 
 use super::ComputeNoise;
 
+/// A generator that can be wrapped by [`Fbm`] and [`Spherical`].
+///
+/// Wrappers import the [`ComputeNoiseShader`](crate::noise::ComputeNoiseShader) function as `noise_fn` and
+/// its struct as `Noise`, so the function must be `fn(uv, noise: Noise) -> f32` with `vec2<f32>` uvs, and
+/// `function_name_3d` the same with `vec3<f32>` uvs. The struct must match the generator's uniform, and
+/// have `seed: u32` and `frequency: f32` fields, which octaves change.
 pub trait ComputeNoiseGenerator: ComputeNoise + Pod {
     fn embed_shaders(app: &mut App);
     fn shader_2d() -> ShaderRef;
     fn shader_3d() -> ShaderRef;
-    fn shader_def() -> ShaderDefVal;
-}
\ No newline at end of file
+}
+
+/// Shaders that wrap another generator, the generator's noise function is imported for each one.
+pub(crate) struct WrapperShaders {
+    pub fbm: Handle<Shader>,
+    pub cube: Handle<Shader>,
+    pub spherical: Handle<Shader>,
+}
+
+impl WrapperShaders {
+    /// Returns [`ComputeNoiseError::MissingNoiseStruct`] if `T` has no struct for the wrappers to import.
+    pub fn new<T: ComputeNoiseGenerator>(shaders: &mut Assets<Shader>) -> Result<Self, ComputeNoiseError> {
+        Ok(Self {
+            fbm: shaders.add(wrapper_shader::<T>(include_str!("shaders/fbm.wgsl"), "fbm.wgsl")?),
+            cube: shaders.add(wrapper_shader::<T>(include_str!("shaders/cube.wgsl"), "cube.wgsl")?),
+            spherical: shaders.add(wrapper_shader::<T>(include_str!("shaders/spherical.wgsl"), "spherical.wgsl")?),
+        })
+    }
+}
+
+/// Prepends imports of `T`'s noise function to `source`, the 2D one when the `2D` shader def is set.
+fn wrapper_shader<T: ComputeNoiseGenerator>(source: &str, file_name: &str) -> Result<Shader, ComputeNoiseError> {
+    let struct_name = T::struct_name().ok_or(ComputeNoiseError::MissingNoiseStruct(T::type_path()))?;
+    let import = |import_path, function_name| format!("#import {import_path}::{{{struct_name} as Noise, {function_name} as noise_fn}}");
+
+    Ok(Shader::from_wgsl(
+        format!(
+            "#ifdef 2D\n    {}\n#else\n    {}\n#endif\n\n{source}",
+            import(T::import_path(), T::function_name()),
+            import(T::import_path_3d(), T::function_name_3d()),
+        ),
+        format!("bevy_compute_noise/noise/generators/shaders/{file_name} ({})", T::type_path()),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn wrapper_imports_noise_fn() {
+        let shader = wrapper_shader::<Perlin>("fn main() {}", "test.wgsl").unwrap();
+        assert_eq!(shader.source.as_str(), "#ifdef 2D
+    #import bevy_compute_noise::perlin::{Perlin as Noise, perlin_2d as noise_fn}
+#else
+    #import bevy_compute_noise::perlin::{Perlin as Noise, perlin_3d as noise_fn}
+#endif
+
+fn main() {}");
+    }
+}
@@ -1,22 +1,28 @@
-use bevy::{asset::embedded_asset, prelude::*, render::{render_resource::{Buffer, BufferInitDescriptor, BufferUsages, ShaderDefVal, ShaderRef}, renderer::RenderDevice}};
+use bevy::{asset::embedded_asset, prelude::*, render::render_resource::{Buffer, ShaderRef}};
 use bytemuck::{Pod, Zeroable};
+use serde::{Deserialize, Serialize};
 
-use crate::{render::pipeline::NoiseOp, shader::ComputeNoiseShader};
+use crate::{buffer_pool::ComputeNoiseBufferPool, image::ComputeNoiseSize, render::pipeline::NoiseOp, shader::ComputeNoiseShader};
 
 use super::{ComputeNoise, ComputeNoiseGenerator};
 
-#[derive(Clone, Copy, Reflect, PartialEq, Debug, Pod, Zeroable)]
-#[reflect(Default)]
+#[derive(Clone, Copy, Reflect, PartialEq, Debug, Pod, Zeroable, Serialize, Deserialize)]
+#[reflect(Default, Serialize, Deserialize)]
 #[repr(C)]
 pub struct Perlin {
     pub seed: u32,
     pub frequency: f32,
-    pub flags: u32,
+    pub flags: PerlinFlags,
 }
 
+#[derive(Clone, Copy, Reflect, PartialEq, Eq, Hash, Debug, Pod, Zeroable, Serialize, Deserialize)]
+#[reflect(Default, Debug, PartialEq, Hash, Serialize, Deserialize)]
+#[serde(transparent)]
+#[repr(transparent)]
+pub struct PerlinFlags(u32);
+
 bitflags::bitflags! {
-    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
-    pub struct PerlinFlags: u32 {
+    impl PerlinFlags: u32 {
         const TILEABLE = 1 << 0;
         const REMAP = 1 << 1;
         const INTERPOLATE_CUBIC = 1 << 2; // quintic interpolation is default
@@ -25,7 +31,7 @@ bitflags::bitflags! {
 
 impl Default for PerlinFlags {
     fn default() -> Self {
-        Self::from_bits_retain(PerlinFlags::REMAP.bits())
+        Self::REMAP
     }
 }
 
@@ -34,7 +40,7 @@ impl Default for Perlin {
         Self {
             seed: 0,
             frequency: 5.0,
-            flags: PerlinFlags::default().bits(),
+            flags: PerlinFlags::default(),
         }
     }
 }
@@ -42,15 +48,9 @@ impl Default for Perlin {
 impl ComputeNoise for Perlin {
     const NOISE_OP: NoiseOp = NoiseOp::Generator; 
 
-    fn buffers(&self, render_device: &RenderDevice) -> Vec<Buffer> { 
+    fn buffers(&self, pool: &mut ComputeNoiseBufferPool, _size: ComputeNoiseSize) -> Vec<Buffer> { 
         vec![
-            render_device.create_buffer_with_data(
-                &BufferInitDescriptor {
-                    label: Some("perlin2d_buffer"),
-                    contents: &bytemuck::cast_slice(&[self.clone()]),
-                    usage: BufferUsages::UNIFORM | BufferUsages::COPY_DST
-                }
-            )
+            pool.uniform("perlin2d_buffer", bytemuck::cast_slice(&[*self]))
         ]
     }
 }
@@ -68,10 +68,6 @@ impl ComputeNoiseGenerator for Perlin {
         embedded_asset!(app, "shaders/perlin_2d.wgsl");
         embedded_asset!(app, "shaders/perlin_3d.wgsl");
     }
-
-    fn shader_def() -> ShaderDefVal {
-       "PERLIN".into() 
-    }
 }
 
 impl ComputeNoiseShader for Perlin {
@@ -86,4 +82,8 @@ impl ComputeNoiseShader for Perlin {
     fn struct_name() -> Option<&'static str> {
         Some("Perlin")
     }
+
+    fn function_name_3d() -> &'static str {
+        "perlin_3d"
+    }
 }
\ No newline at end of file
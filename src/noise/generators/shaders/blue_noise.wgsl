@@ -0,0 +1,186 @@
+// Blue noise relaxation, see `BlueNoise`. Values are seeded with white noise, relaxed by swapping pairs of
+// texels within a layer, then sorted with a bitonic sort and replaced with their rank in the layer so they're
+// uniformly distributed.
+
+#ifdef ARRAY
+#import bevy_compute_noise::util::texture2d_array as texture
+#else
+#import bevy_compute_noise::util::texture2d as texture
+#endif
+#import bevy_compute_noise::math::{hash33, rand_u}
+#import bevy_compute_noise::dispatch::{dispatch_location, outside_dispatch, dispatch_layer, layer_cell_index}
+
+const SPATIOTEMPORAL: u32 = 1u;
+
+const STAGE_SEED: u32 = 0u;
+const STAGE_RELAX: u32 = 1u;
+const STAGE_SORT: u32 = 2u;
+const STAGE_RANK: u32 = 3u;
+const STAGE_STORE: u32 = 4u;
+
+struct BlueNoise {
+    stage: u32,
+    seed: u32,
+    iteration: u32,
+    flags: u32,
+    sigma: f32,
+    layers: u32,
+    partner_mask: u32,
+}
+@group(0) @binding(1) var<uniform> blue_noise: BlueNoise;
+@group(0) @binding(2) var<storage, read> values_in: array<Cell>;
+@group(0) @binding(3) var<storage, read_write> values_out: array<Cell>;
+
+// Each channel is sorted on its own, so every channel keeps track of where its value started in the layer.
+struct Cell {
+    value: vec4<f32>,
+    index: vec4<u32>,
+}
+
+@compute @workgroup_size(16, 16)
+fn main(@builtin(global_invocation_id) invocation_id: vec3<u32>) {
+    let texel = dispatch_location(invocation_id);
+    if outside_dispatch(texel) { return; }
+    let size = vec2<i32>(textureDimensions(texture));
+    let location = vec2<i32>(texel.xy);
+    let layer = i32(dispatch_layer());
+
+    switch blue_noise.stage {
+        case STAGE_SEED: {
+            let p = vec3<f32>(vec2<f32>(location), f32(u32(layer) + blue_noise.seed * blue_noise.layers));
+            let value = vec4(hash33(p), hash33(p.zxy).x) * 0.5 + 0.5;
+            values_out[layer_cell_index(location, layer, size)] = Cell(value, vec4(u32(location.y * size.x + location.x)));
+        }
+        case STAGE_RELAX: {
+            let value = relax(location, layer, size);
+            values_out[layer_cell_index(location, layer, size)] = Cell(value, vec4(u32(location.y * size.x + location.x)));
+        }
+        case STAGE_SORT: {
+            sort(location, layer, size);
+        }
+        case STAGE_RANK: {
+            rank(location, layer, size);
+        }
+        case STAGE_STORE: {
+            let value = values_in[layer_cell_index(location, layer, size)].value;
+            #ifdef ARRAY
+                textureStore(texture, location, layer, value);
+            #else
+                textureStore(texture, location, value);
+            #endif
+        }
+        default: {}
+    }
+}
+
+fn wrap(location: vec2<i32>, size: vec2<i32>) -> vec2<i32> {
+    return ((location % size) + size) % size;
+}
+
+// Each iteration pairs texels `offset` apart. Along each axis the texture is split into blocks of twice the
+// offset starting at `origin`, and texels in the first half of a block pair with the one in the second half,
+// so no texel is in more than one pair. Returns 1 for the first texel of a pair, -1 for the second and 0 for
+// texels without a partner, near the edge when the size isn't a multiple of the block, or off the diagonal.
+fn pair_role(location: vec2<i32>, offset: vec2<i32>, origin: vec2<i32>, size: vec2<i32>) -> i32 {
+    var first = true;
+    var second = true;
+    for (var axis = 0; axis < 2; axis++) {
+        let step = offset[axis];
+        if step == 0 { continue; }
+
+        let block = 2 * abs(step);
+        let c = (((location[axis] - origin[axis]) % size[axis]) + size[axis]) % size[axis];
+        if c - c % block + block > size[axis] { return 0; }
+
+        let leading = (c % block < abs(step)) == (step > 0);
+        first = first && leading;
+        second = second && !leading;
+    }
+    return select(select(0, -1, second), 1, first);
+}
+
+// Energy of `value` at `location` from its neighbours, skipping the texel it might swap with since the
+// energy between the two doesn't change. Nearby texels with similar values add the most.
+fn energy(location: vec2<i32>, layer: i32, value: vec4<f32>, partner: vec2<i32>, size: vec2<i32>) -> vec4<f32> {
+    let sigma_squared = blue_noise.sigma * blue_noise.sigma;
+    let radius = i32(ceil(blue_noise.sigma * 2.0));
+
+    var energy = vec4(0.0);
+    for (var y = -radius; y <= radius; y++) {
+        for (var x = -radius; x <= radius; x++) {
+            let neighbour = wrap(location + vec2(x, y), size);
+            if (x == 0 && y == 0) || all(neighbour == partner) { continue; }
+
+            let weight = exp(-f32(x * x + y * y) / sigma_squared);
+            energy += weight * exp(-sqrt(abs(value - values_in[layer_cell_index(neighbour, layer, size)].value)));
+        }
+    }
+
+    // The same texel in the other frames, which wrap around.
+    if (blue_noise.flags & SPATIOTEMPORAL) != 0u {
+        let layers = i32(blue_noise.layers);
+        for (var t = 1; t < layers; t++) {
+            let distance = f32(min(t, layers - t));
+            let weight = exp(-distance * distance / sigma_squared);
+            energy += weight * exp(-sqrt(abs(value - values_in[layer_cell_index(location, (layer + t) % layers, size)].value)));
+        }
+    }
+
+    return energy;
+}
+
+fn relax(location: vec2<i32>, layer: i32, size: vec2<i32>) -> vec4<f32> {
+    let value = values_in[layer_cell_index(location, layer, size)].value;
+
+    // Every texel in a layer picks the same pairing for this iteration. Layers pick their own, swapping
+    // the same texels in every frame at once would undo what the frames around them just relaxed.
+    var state = blue_noise.seed * 747796405u + blue_noise.iteration * 2891336453u + u32(layer) * 1597334677u;
+    let directions = array(vec2(1, 0), vec2(0, 1), vec2(1, 1), vec2(1, -1));
+    let offset = directions[rand_u(&state) % 4u] * i32(1u + rand_u(&state) % 3u);
+    let origin = vec2<i32>(vec2(rand_u(&state), rand_u(&state)) % vec2<u32>(size));
+
+    let role = pair_role(location, offset, origin, size);
+    if role == 0 { return value; }
+    let partner = wrap(location + offset * role, size);
+    let partner_value = values_in[layer_cell_index(partner, layer, size)].value;
+
+    // Both texels of a pair have to make the same decision, so the sums are always in the same order.
+    let first = select(partner, location, role == 1);
+    let second = select(location, partner, role == 1);
+    let first_value = select(partner_value, value, role == 1);
+    let second_value = select(value, partner_value, role == 1);
+
+    let keep = energy(first, layer, first_value, second, size) + energy(second, layer, second_value, first, size);
+    let swap = energy(first, layer, second_value, second, size) + energy(second, layer, first_value, first, size);
+    return select(value, partner_value, swap < keep);
+}
+
+// One compare and swap of a bitonic sort, in place in `values_out`. Every texel pairs with the one at its index xor
+// `partner_mask`, and the lower index of the pair swaps them if they're out of order. Layers that aren't a power of two
+// in size sort as if they were padded with infinity, which never moves, so pairs past the end are left alone.
+// Ties are broken by where the values started, so every rank in the layer is used once.
+fn sort(location: vec2<i32>, layer: i32, size: vec2<i32>) {
+    let index = u32(location.y * size.x + location.x);
+    let partner = index ^ blue_noise.partner_mask;
+    if partner <= index || partner >= u32(size.x * size.y) { return; }
+
+    let start = u32(layer * size.x * size.y);
+    let low = values_out[start + index];
+    let high = values_out[start + partner];
+    let swap = (high.value < low.value) | ((high.value == low.value) & (high.index < low.index));
+    values_out[start + index] = Cell(select(low.value, high.value, swap), select(low.index, high.index, swap));
+    values_out[start + partner] = Cell(select(high.value, low.value, swap), select(high.index, low.index, swap));
+}
+
+// Once sorted, each texel's position in the layer is the rank of the value that started at `index`.
+fn rank(location: vec2<i32>, layer: i32, size: vec2<i32>) {
+    let count = size.x * size.y;
+    let start = u32(layer * count);
+    let index = values_in[start + u32(location.y * size.x + location.x)].index;
+    let rank = (f32(location.y * size.x + location.x) + 0.5) / f32(count);
+
+    values_out[start + index.x].value.x = rank;
+    values_out[start + index.y].value.y = rank;
+    values_out[start + index.z].value.z = rank;
+    values_out[start + index.w].value.w = rank;
+}
@@ -0,0 +1,69 @@
+// Volumetric cloud textures, `CloudShape` by default and `CloudDetail` with the `DETAIL` shader def.
+// Everything tiles, so frequencies are whole numbers of cells across the texture.
+
+#import bevy_compute_noise::util::texture3d as texture
+#import bevy_compute_noise::math::remap
+#import bevy_compute_noise::dispatch::{dispatch_location, outside_dispatch}
+#import bevy_compute_noise::perlin::Perlin
+#import bevy_compute_noise::worley::Worley
+#import bevy_compute_noise::fbm::{Fbm, fbm_perlin_3d, fbm_worley_3d, INVERT}
+
+const PERLIN_TILEABLE_REMAP: u32 = 3u;
+const WORLEY_TILEABLE: u32 = 1u;
+
+#ifdef DETAIL
+struct Config {
+    seed: u32,
+    frequency: f32,
+}
+#else
+struct Config {
+    seed: u32,
+    perlin_frequency: f32,
+    perlin_octaves: u32,
+    worley_frequency: f32,
+}
+#endif
+@group(0) @binding(1) var<uniform> config: Config;
+
+// Three octaves of inverted worley, so cells are bright and their edges dark.
+fn worley_fbm(uv: vec3<f32>, seed: u32, frequency: f32) -> f32 {
+    return saturate(fbm_worley_3d(uv, Worley(seed, frequency, WORLEY_TILEABLE), Fbm(3u, 2.0, 0.5, INVERT)));
+}
+
+// Whole numbers of cells across the texture, at least one so there's something to tile.
+fn cells(frequency: f32) -> f32 {
+    return max(floor(frequency), 1.0);
+}
+
+@compute @workgroup_size(8, 8, 4)
+fn main(@builtin(global_invocation_id) invocation_id: vec3<u32>) {
+    let texel = dispatch_location(invocation_id);
+    if outside_dispatch(texel) { return; }
+    let uv = vec3<f32>(texel) / vec3<f32>(textureDimensions(texture));
+
+#ifdef DETAIL
+    let frequency = cells(config.frequency);
+    let value = vec4(
+        worley_fbm(uv, config.seed, frequency),
+        worley_fbm(uv, config.seed, frequency * 2.0),
+        worley_fbm(uv, config.seed, frequency * 4.0),
+        1.0,
+    );
+#else
+    let frequency = cells(config.worley_frequency);
+    let worley = worley_fbm(uv, config.seed, frequency);
+    let perlin_noise = Perlin(config.seed, cells(config.perlin_frequency), PERLIN_TILEABLE_REMAP);
+    let perlin = fbm_perlin_3d(uv, perlin_noise, Fbm(max(config.perlin_octaves, 1u), 2.0, 0.5, 0u));
+    // Dilates the perlin noise by the worley noise, giving billowy shapes that still connect.
+    let perlin_worley = saturate(remap(perlin, worley - 1.0, 1.0, 0.0, 1.0));
+    let value = vec4(
+        perlin_worley,
+        worley,
+        worley_fbm(uv, config.seed, frequency * 2.0),
+        worley_fbm(uv, config.seed, frequency * 4.0),
+    );
+#endif
+
+    textureStore(texture, texel, value);
+}
@@ -0,0 +1,21 @@
+#import bevy_compute_noise::util::texture2d_array as texture
+#import bevy_compute_noise::math::cube_direction
+#import bevy_compute_noise::dispatch::{dispatch_location, outside_dispatch, dispatch_layer}
+
+// `Noise` and `noise_fn` are imported from the wrapped generator, see `WrapperShaders`.
+
+@group(0) @binding(1) var<uniform> noise: Noise;
+
+// Each face is written as a layer, sampling 3D noise on the unit sphere so the faces line up.
+@compute @workgroup_size(16, 16)
+fn main(@builtin(global_invocation_id) invocation_id: vec3<u32>) {
+    let texel = dispatch_location(invocation_id);
+    if outside_dispatch(texel) { return; }
+    let location = texel.xy;
+    let texture_size = textureDimensions(texture);
+    let uv = (vec2<f32>(location) + 0.5) / vec2<f32>(texture_size);
+
+    let direction = cube_direction(dispatch_layer(), uv);
+    let value = noise_fn(direction * 0.5 + 0.5, noise);
+    textureStore(texture, location, dispatch_layer(), vec4<f32>(value, 0.0, 0.0, 1.0));
+}
@@ -0,0 +1,65 @@
+// Template for `ExpressionNoise`, `{{EXPRESSION}}` is replaced with the expression's source.
+#import bevy_compute_noise::dispatch::{dispatch_location, outside_dispatch, dispatch_layer}
+#import bevy_compute_noise::perlin::{Perlin, perlin_3d}
+#import bevy_compute_noise::worley::{Worley, worley_3d}
+#ifdef 3D
+    #import bevy_compute_noise::util::texture3d as texture
+#else ifdef ARRAY
+    #import bevy_compute_noise::util::texture2d_array as texture
+#else
+    #import bevy_compute_noise::util::texture2d as texture
+#endif
+#ifdef CUBE
+    #import bevy_compute_noise::math::cube_direction
+#endif
+
+@group(0) @binding(1) var<storage, read> params: array<f32>;
+
+fn perlin(p: vec3<f32>, frequency: f32) -> f32 {
+    return perlin_3d(p, Perlin(0u, frequency, 2u));
+}
+
+fn worley(p: vec3<f32>, frequency: f32) -> f32 {
+    return worley_3d(p, Worley(0u, frequency, 0u));
+}
+
+#ifdef 3D
+fn expression(uv: vec3<f32>, p: vec3<f32>, layer: u32) -> f32 {
+#else
+fn expression(uv: vec2<f32>, p: vec3<f32>, layer: u32) -> f32 {
+#endif
+    return {{EXPRESSION}};
+}
+
+#ifdef 3D
+    @compute @workgroup_size(8, 8, 4)
+#else
+    @compute @workgroup_size(16, 16)
+#endif
+fn main(@builtin(global_invocation_id) invocation_id: vec3<u32>) {
+    let texel = dispatch_location(invocation_id);
+    if outside_dispatch(texel) { return; }
+
+    let texture_size = textureDimensions(texture);
+    #ifdef 3D
+        let location = texel;
+        let uv = vec3<f32>(location) / vec3<f32>(texture_size);
+        let p = uv;
+    #else
+        let location = texel.xy;
+        let uv = vec2<f32>(location) / vec2<f32>(texture_size);
+        #ifdef CUBE
+            let face_uv = (vec2<f32>(location) + 0.5) / vec2<f32>(texture_size);
+            let p = cube_direction(dispatch_layer(), face_uv) * 0.5 + 0.5;
+        #else
+            let p = vec3<f32>(uv, 0.0);
+        #endif
+    #endif
+
+    let value = expression(uv, p, dispatch_layer());
+    #ifdef ARRAY
+        textureStore(texture, location, dispatch_layer(), vec4<f32>(value, 0.0, 0.0, 1.0));
+    #else
+        textureStore(texture, location, vec4<f32>(value, 0.0, 0.0, 1.0));
+    #endif
+}
@@ -1,24 +1,18 @@
-#ifdef 2D
-    #import bevy_compute_noise::util::texture2d as texture
-    #ifdef PERLIN
-        #import bevy_compute_noise::perlin_2d::{Perlin as Noise, perlin_2d as noise_fn}
-    #endif
-    #ifdef WORLEY
-        #import bevy_compute_noise::worley_2d::{Worley as Noise, worley_2d as noise_fn}
-    #endif
-#endif
+#import bevy_compute_noise::dispatch::{dispatch_location, outside_dispatch, dispatch_layer}
+#import bevy_compute_noise::fbm::{Fbm, first_amplitude, octave}
+
+// `Noise` and `noise_fn` are imported from the wrapped generator, see `WrapperShaders`.
+
 #ifdef 3D
     #import bevy_compute_noise::util::texture3d as texture
-    #ifdef PERLIN
-        #import bevy_compute_noise::perlin_3d::{Perlin as Noise, perlin_3d as noise_fn}
-    #endif
-    #ifdef WORLEY
-        #import bevy_compute_noise::worley_3d::{Worley as Noise, worley_3d as noise_fn}
-    #endif
+#else ifdef ARRAY
+    #import bevy_compute_noise::util::texture2d_array as texture
+#else
+    #import bevy_compute_noise::util::texture2d as texture
+#endif
+#ifdef CUBE
+    #import bevy_compute_noise::math::cube_direction
 #endif
-
-const INVERT: u32 = 1u;
-const BILLOWY: u32 = 2u;
 
 struct Config {
     octaves: u32,
@@ -30,42 +24,51 @@ struct Config {
 @group(0) @binding(1) var<uniform> config: Config;
 
 #ifdef 2D
-    @compute @workgroup_size(32, 32)
+    @compute @workgroup_size(16, 16)
+#endif
+#ifdef CUBE
+    @compute @workgroup_size(16, 16)
 #endif
 #ifdef 3D
-    @compute @workgroup_size(8, 8, 8)
+    @compute @workgroup_size(8, 8, 4)
 #endif
 fn main(@builtin(global_invocation_id) invocation_id: vec3<u32>) {
+    let texel = dispatch_location(invocation_id);
+    if outside_dispatch(texel) { return; }
+
     let texture_size = textureDimensions(texture);
     #ifdef 2D
-        let location = invocation_id.xy; 
+        let location = texel.xy;
         let uv = vec2<f32>(location) / vec2<f32>(texture_size);
     #endif
+    #ifdef CUBE
+        let location = texel.xy;
+        let face_uv = (vec2<f32>(location) + 0.5) / vec2<f32>(texture_size);
+        let uv = cube_direction(dispatch_layer(), face_uv) * 0.5 + 0.5;
+    #endif
     #ifdef 3D
-        let location = invocation_id.xyz; 
+        let location = texel;
         let uv = vec3<f32>(location) / vec3<f32>(texture_size);
     #endif
 
+    let fbm = Fbm(config.octaves, config.lacunarity, config.persistence, config.flags);
     var value = 0.0;
-
-    let max_amplitude = (1.0 - pow(config.persistence, f32(config.octaves))) / (1.0 - config.persistence);
-    var amplitude = 1.0 / max_amplitude;
+    var amplitude = first_amplitude(fbm);
     var noise = config.noise;
+    #ifdef 2D
+        // Every layer of an array texture gets its own seed.
+        noise.seed += dispatch_layer();
+    #endif
 
-    for(var i = 0u; i < config.octaves; i++) {
-        var octave_value = noise_fn(uv, noise);
-        if (config.flags & INVERT) != 0u {
-           octave_value = 1.0 - octave_value;
-        }
-        if (config.flags & BILLOWY) != 0 {
-            octave_value = abs(octave_value);
-        }
-
-       value += octave_value * amplitude;
-       
-       noise.frequency *= config.lacunarity;
-       amplitude *= config.persistence;
+    for (var i = 0u; i < fbm.octaves; i++) {
+        value += octave(noise_fn(uv, noise), fbm) * amplitude;
+        noise.frequency *= fbm.lacunarity;
+        amplitude *= fbm.persistence;
     }
 
-    textureStore(texture, location, vec4<f32>(value, 0.0, 0.0, 1.0));
+    #ifdef ARRAY
+        textureStore(texture, location, dispatch_layer(), vec4<f32>(value, 0.0, 0.0, 1.0));
+    #else
+        textureStore(texture, location, vec4<f32>(value, 0.0, 0.0, 1.0));
+    #endif
 }
\ No newline at end of file
@@ -0,0 +1,46 @@
+#ifdef ARRAY
+#import bevy_compute_noise::util::texture2d_array as texture
+#else
+#import bevy_compute_noise::util::texture2d as texture
+#endif
+#import bevy_compute_noise::math::{cube_direction, equirectangular_direction}
+#import bevy_compute_noise::dispatch::{dispatch_location, outside_dispatch, dispatch_layer}
+
+// `Noise` and `noise_fn` are imported from the wrapped generator, see `WrapperShaders`.
+
+struct Config {
+    vertex_count: u32,
+    _padding0: u32,
+    _padding1: u32,
+    _padding2: u32,
+    noise: Noise,
+}
+@group(0) @binding(1) var<uniform> config: Config;
+@group(0) @binding(2) var<storage, read> vertices: array<vec4<f32>>;
+
+@compute @workgroup_size(16, 16)
+fn main(@builtin(global_invocation_id) invocation_id: vec3<u32>) {
+    let texel = dispatch_location(invocation_id);
+    if outside_dispatch(texel) { return; }
+    let location = texel.xy;
+    let texture_size = textureDimensions(texture);
+    let uv = (vec2<f32>(location) + 0.5) / vec2<f32>(texture_size);
+
+    #ifdef CUBE
+        let direction = cube_direction(dispatch_layer(), uv);
+    #else
+        var direction = equirectangular_direction(uv);
+        if config.vertex_count != 0u {
+            let index = location.y * texture_size.x + location.x;
+            if index >= config.vertex_count { return; }
+            direction = vertices[index].xyz;
+        }
+    #endif
+
+    let value = noise_fn(direction * 0.5 + 0.5, config.noise);
+    #ifdef ARRAY
+        textureStore(texture, location, dispatch_layer(), vec4<f32>(value, 0.0, 0.0, 1.0));
+    #else
+        textureStore(texture, location, vec4<f32>(value, 0.0, 0.0, 1.0));
+    #endif
+}
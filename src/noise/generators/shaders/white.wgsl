@@ -0,0 +1,45 @@
+// Independent random values in every channel of every texel, `White` noise.
+// Written to 2D textures by default, 2D array textures with the `ARRAY` shader def and 3D textures with the `3D` one.
+
+#ifdef 3D
+#import bevy_compute_noise::util::texture3d as texture
+#else ifdef ARRAY
+#import bevy_compute_noise::util::texture2d_array as texture
+#else
+#import bevy_compute_noise::util::texture2d as texture
+#endif
+#import bevy_compute_noise::math::hash33
+#import bevy_compute_noise::dispatch::{dispatch_location, outside_dispatch, dispatch_layer}
+
+struct White {
+    seed: u32,
+}
+@group(0) @binding(1) var<uniform> white: White;
+
+#ifdef 3D
+@compute @workgroup_size(8, 8, 4)
+#else
+@compute @workgroup_size(16, 16)
+#endif
+fn main(@builtin(global_invocation_id) invocation_id: vec3<u32>) {
+    let texel = dispatch_location(invocation_id);
+    if outside_dispatch(texel) { return; }
+
+    // Hashed as the texel's position in a stack of slices, where each seed starts the next stack.
+#ifdef 3D
+    let z = texel.z + white.seed * textureDimensions(texture).z;
+#else
+    // Every layer of an array texture gets its own seed.
+    let z = white.seed + dispatch_layer();
+#endif
+    let p = vec3<f32>(vec2<f32>(texel.xy), f32(z));
+    let value = vec4(hash33(p), hash33(p.zxy).x) * 0.5 + 0.5;
+
+#ifdef 3D
+    textureStore(texture, texel, value);
+#else ifdef ARRAY
+    textureStore(texture, texel.xy, dispatch_layer(), value);
+#else
+    textureStore(texture, texel.xy, value);
+#endif
+}
@@ -0,0 +1,98 @@
+use bevy::{prelude::*, render::{mesh::VertexAttributeValues, render_resource::Buffer}};
+use serde::{Deserialize, Serialize};
+use crate::{buffer_pool::ComputeNoiseBufferPool, image::{ComputeNoiseImage, ComputeNoiseSize}, render::pipeline::NoiseOp, shader::ComputeNoiseShader};
+
+use super::{ComputeNoise, ComputeNoiseGenerator};
+
+/// Widest image [`Spherical::vertex_image`] creates, longer vertex lists wrap onto more rows.
+const MAX_VERTEX_IMAGE_WIDTH: u32 = 1024;
+
+/// Samples the 3D version of `noise` on the unit sphere, so there are no seams or pinched poles.
+///
+/// 2D textures get an equirectangular projection and cube textures get each face. Made with
+/// [`Spherical::from_mesh`], the noise at each vertex's direction is written instead, vertex `i`
+/// going to texel `(i % width, i / width)` of an image from [`Spherical::vertex_image`].
+#[derive(Clone, Default, Reflect, Serialize, Deserialize)]
+#[reflect(Default)]
+pub struct Spherical<T: ComputeNoiseGenerator> {
+    pub noise: T,
+    /// Directions to sample, the equirectangular projection is written when this is empty.
+    pub vertices: Vec<Vec3>,
+}
+
+impl<T: ComputeNoiseGenerator> Spherical<T> {
+    pub fn new(noise: T) -> Self {
+        Self { noise, vertices: Vec::new() }
+    }
+
+    /// Samples the noise at the direction of each of `mesh`'s vertex positions from its origin.
+    /// Returns `None` if the mesh has no `Float32x3` positions.
+    pub fn from_mesh(noise: T, mesh: &Mesh) -> Option<Self> {
+        let Some(VertexAttributeValues::Float32x3(positions)) = mesh.attribute(Mesh::ATTRIBUTE_POSITION) else { return None };
+
+        Some(Self {
+            noise,
+            vertices: positions.iter().map(|position| Vec3::from(*position).normalize_or_zero()).collect(),
+        })
+    }
+
+    /// Size of an image with a texel for every vertex.
+    pub fn vertex_image_size(&self) -> ComputeNoiseSize {
+        let vertex_count = (self.vertices.len() as u32).max(1);
+        let width = vertex_count.min(MAX_VERTEX_IMAGE_WIDTH);
+        ComputeNoiseSize::D2(width, vertex_count.div_ceil(width))
+    }
+
+    pub fn vertex_image(&self) -> Image {
+        ComputeNoiseImage::create_image(self.vertex_image_size())
+    }
+}
+
+impl<T: ComputeNoiseGenerator> ComputeNoise for Spherical<T> {
+    const NOISE_OP: NoiseOp = NoiseOp::Generator;
+
+    fn buffers(&self, pool: &mut ComputeNoiseBufferPool, _size: ComputeNoiseSize) -> Vec<Buffer> {
+        // Storage buffers can't be empty, the shader ignores this one when `vertex_count` is 0.
+        let vertices: Vec<[f32; 4]> = match self.vertices.is_empty() {
+            true => vec![[0.0; 4]],
+            false => self.vertices.iter().map(|vertex| vertex.extend(0.0).to_array()).collect(),
+        };
+
+        vec![
+            pool.uniform("spherical_noise_buffer", &[
+                bytemuck::cast_slice(&[self.vertices.len() as u32, 0, 0, 0]),
+                bytemuck::cast_slice(&[self.noise]),
+            ].concat()),
+            pool.storage("spherical_vertices_buffer", bytemuck::cast_slice(&vertices)),
+        ]
+    }
+}
+
+impl<T: ComputeNoiseGenerator> ComputeNoiseShader for Spherical<T> {
+    fn function_name() -> &'static str {
+        "spherical"
+    }
+
+    fn import_path() -> &'static str {
+        "bevy_compute_noise::spherical"
+    }
+
+    fn struct_name() -> Option<&'static str> {
+        Some("Config")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn vertex_image_wraps_rows() {
+        use crate::noise::generators::Perlin;
+
+        let spherical = |count| Spherical { noise: Perlin::default(), vertices: vec![Vec3::Y; count] };
+        assert_eq!(spherical(0).vertex_image_size(), ComputeNoiseSize::D2(1, 1));
+        assert_eq!(spherical(642).vertex_image_size(), ComputeNoiseSize::D2(642, 1));
+        assert_eq!(spherical(2500).vertex_image_size(), ComputeNoiseSize::D2(1024, 3));
+    }
+}
@@ -0,0 +1,40 @@
+use bevy::{prelude::*, render::render_resource::Buffer};
+use bytemuck::{Pod, Zeroable};
+use serde::{Deserialize, Serialize};
+
+use crate::{buffer_pool::ComputeNoiseBufferPool, image::ComputeNoiseSize, render::pipeline::NoiseOp, shader::ComputeNoiseShader};
+
+use super::ComputeNoise;
+
+/// A random value per texel in each channel, uncorrelated with its neighbours and the other channels.
+/// Supports 2D, 2D array and 3D textures, and tiles since no texel depends on another.
+#[derive(Clone, Copy, Reflect, PartialEq, Debug, Default, Pod, Zeroable, Serialize, Deserialize)]
+#[reflect(Default, Serialize, Deserialize)]
+#[repr(C)]
+pub struct White {
+    pub seed: u32,
+}
+
+impl ComputeNoise for White {
+    const NOISE_OP: NoiseOp = NoiseOp::Generator;
+
+    fn buffers(&self, pool: &mut ComputeNoiseBufferPool, _size: ComputeNoiseSize) -> Vec<Buffer> {
+        vec![
+            pool.uniform("white_buffer", bytemuck::cast_slice(&[*self])),
+        ]
+    }
+}
+
+impl ComputeNoiseShader for White {
+    fn function_name() -> &'static str {
+        "white"
+    }
+
+    fn import_path() -> &'static str {
+        "bevy_compute_noise::white"
+    }
+
+    fn struct_name() -> Option<&'static str> {
+        Some("White")
+    }
+}
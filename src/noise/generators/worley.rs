@@ -1,22 +1,28 @@
-use bevy::{asset::embedded_asset, prelude::*, render::{render_resource::{Buffer, BufferInitDescriptor, BufferUsages, ShaderDefVal, ShaderRef}, renderer::RenderDevice}};
+use bevy::{asset::embedded_asset, prelude::*, render::render_resource::{Buffer, ShaderRef}};
 use bytemuck::{Pod, Zeroable};
+use serde::{Deserialize, Serialize};
 
-use crate::{render::pipeline::NoiseOp, shader::ComputeNoiseShader};
+use crate::{buffer_pool::ComputeNoiseBufferPool, image::ComputeNoiseSize, render::pipeline::NoiseOp, shader::ComputeNoiseShader};
 
 use super::{ComputeNoise, ComputeNoiseGenerator};
 
-#[derive(Clone, Copy, Reflect, PartialEq, Debug, Pod, Zeroable)]
-#[reflect(Default)]
+#[derive(Clone, Copy, Reflect, PartialEq, Debug, Pod, Zeroable, Serialize, Deserialize)]
+#[reflect(Default, Serialize, Deserialize)]
 #[repr(C)]
 pub struct Worley {
     pub seed: u32,
     pub frequency: f32,
-    pub flags: u32,
+    pub flags: WorleyFlags,
 }
 
-bitflags::bitflags! { 
-    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
-    pub struct WorleyFlags: u32 {
+#[derive(Clone, Copy, Reflect, PartialEq, Eq, Hash, Debug, Default, Pod, Zeroable, Serialize, Deserialize)]
+#[reflect(Default, Debug, PartialEq, Hash, Serialize, Deserialize)]
+#[serde(transparent)]
+#[repr(transparent)]
+pub struct WorleyFlags(u32);
+
+bitflags::bitflags! {
+    impl WorleyFlags: u32 {
         const TILEABLE = 1 << 0;
     }
 }
@@ -26,7 +32,7 @@ impl Default for Worley {
         Self {
             seed: 0,
             frequency: 5.0,
-            flags: 0,
+            flags: WorleyFlags::empty(),
         }
     }
 }
@@ -34,15 +40,9 @@ impl Default for Worley {
 impl ComputeNoise for Worley {
     const NOISE_OP: NoiseOp = NoiseOp::Generator; 
 
-    fn buffers(&self, render_device: &RenderDevice) -> Vec<Buffer> {
+    fn buffers(&self, pool: &mut ComputeNoiseBufferPool, _size: ComputeNoiseSize) -> Vec<Buffer> {
         vec![
-            render_device.create_buffer_with_data(
-                &BufferInitDescriptor {
-                    label: Some("worley2d_points_buffer"),
-                    contents: &bytemuck::cast_slice(&[self.clone()]),
-                    usage: BufferUsages::UNIFORM | BufferUsages::COPY_DST
-                }
-            ),
+            pool.uniform("worley2d_points_buffer", bytemuck::cast_slice(&[*self])),
         ] 
     }
 }
@@ -60,10 +60,6 @@ impl ComputeNoiseGenerator for Worley {
         embedded_asset!(app, "shaders/worley_2d.wgsl");
         embedded_asset!(app, "shaders/worley_3d.wgsl");
     }
-
-    fn shader_def() -> ShaderDefVal {
-       "WORLEY".into() 
-    }
 }
 
 impl ComputeNoiseShader for Worley {
@@ -78,4 +74,8 @@ impl ComputeNoiseShader for Worley {
     fn struct_name() -> Option<&'static str> {
         Some("Worley")
     }
+
+    fn function_name_3d() -> &'static str {
+        "worley_3d"
+    }
 }
\ No newline at end of file
@@ -1,39 +1,67 @@
 use std::any::{Any, TypeId};
 
-use bevy::{prelude::*, reflect::{FromReflect, GetTypeRegistration, TypePath, Typed}, render::{render_resource::Buffer, renderer::RenderDevice}};
+use bevy::{prelude::*, reflect::{FromReflect, GetTypeRegistration, TypePath, Typed}, render::render_resource::Buffer};
 
-use crate::{noise_queue::QueueNoiseOp, render::pipeline::NoiseOp, shader::ComputeNoiseShader};
+use crate::{buffer_pool::ComputeNoiseBufferPool, error::ComputeNoiseError, image::ComputeNoiseSize, noise_queue::QueueNoiseOp};
 
 pub mod generators;
 pub mod modifiers;
 
+pub use crate::{render::pipeline::NoiseOp, shader::ComputeNoiseShader};
+
 pub trait ComputeNoise: Sync + Send + 'static + Default + Clone + TypePath + FromReflect + GetTypeRegistration + Typed + ComputeNoiseShader {
     const NOISE_OP: NoiseOp;
+    /// Multi-pass noise that reads neighbouring texels from its own storage buffers needs them filled
+    /// over the whole texture, so it can't be queued with a region.
+    const SUPPORTS_REGION: bool = true;
+    /// Noise that only makes sense on 2D textures, like erosion of a heightmap, has no 3D pipeline.
+    /// Array and cube textures still work, their layers are 2D.
+    const SUPPORTS_3D: bool = true;
+
+    fn buffers(&self, pool: &mut ComputeNoiseBufferPool, size: ComputeNoiseSize) -> Vec<Buffer>;
+
+    /// Sequences are built before the size of their texture is known, so multi-pass noise whose number of passes
+    /// depends on it, like [`BlueNoise`](generators::BlueNoise)'s sort, returns false for passes `size` doesn't need.
+    fn runs_on(&self, _size: ComputeNoiseSize) -> bool {
+        true
+    }
 
-    fn buffers(&self, render_device: &RenderDevice) -> Vec<Buffer>;
+    /// Noise whose shader depends on its values, like [`ExpressionNoise`](generators::ExpressionNoise),
+    /// returns a different variant for each shader so they get their own pipelines.
+    fn pipeline_variant(&self) -> u64 {
+        0
+    }
 
-    fn input_image(self, input: Handle<Image>) -> QueueNoiseOp {
+    fn input_image(self, input: Handle<Image>) -> Result<QueueNoiseOp, ComputeNoiseError> {
         let erased = ErasedComputeNoise::from(self);
         match Self::NOISE_OP {
-            NoiseOp::Modifier => QueueNoiseOp::Modify(input, erased),
-            NoiseOp::Combiner => panic!("Use with_inputs for combiners"),
-            _ => panic!("Operation doesn't support input images"),
+            NoiseOp::Modifier => Ok(QueueNoiseOp::Modify(input, erased)),
+            NoiseOp::Combiner => Err(ComputeNoiseError::NeedsInputImages(Self::type_path())),
+            _ => Err(ComputeNoiseError::NoInputImage(Self::type_path())),
         }
     }
 
-    fn input_images(self, input1: Handle<Image>, input2: Handle<Image>) -> QueueNoiseOp {
+    fn input_images(self, input1: Handle<Image>, input2: Handle<Image>) -> Result<QueueNoiseOp, ComputeNoiseError> {
         let erased = ErasedComputeNoise::from(self);
         match Self::NOISE_OP {
-            NoiseOp::Combiner => QueueNoiseOp::Combine(input1, input2, erased),
-            _ => panic!("Operation doesn't support multiple inputs"),
+            NoiseOp::Combiner => Ok(QueueNoiseOp::Combine(input1, input2, erased)),
+            _ => Err(ComputeNoiseError::NoInputImages(Self::type_path())),
         }
     }
 }
 
+type BuffersFn = Box<dyn Fn(&mut ComputeNoiseBufferPool, ComputeNoiseSize) -> Vec<Buffer> + Send + Sync>;
+type RunsOnFn = fn(&(dyn Any + Send + Sync), ComputeNoiseSize) -> bool;
+
 pub struct ErasedComputeNoise {
     noise_data: Box<dyn Any + Send + Sync>,
-    buffers_fn: Box<dyn Fn(&RenderDevice) -> Vec<Buffer> + Send + Sync>,
+    buffers_fn: BuffersFn,
+    runs_on_fn: RunsOnFn,
     pub type_id: TypeId,
+    pub type_name: &'static str,
+    pub variant: u64,
+    pub supports_region: bool,
+    pub supports_3d: bool,
 
     pub struct_name: Option<&'static str>,
     pub function_name: &'static str,
@@ -45,8 +73,12 @@ impl ErasedComputeNoise {
         self.noise_data.downcast_ref::<T>()
     }
 
-    pub fn buffers(&self, render_device: &RenderDevice) -> Vec<Buffer> {
-        (self.buffers_fn)(render_device)
+    pub fn buffers(&self, pool: &mut ComputeNoiseBufferPool, size: ComputeNoiseSize) -> Vec<Buffer> {
+        (self.buffers_fn)(pool, size)
+    }
+
+    pub fn runs_on(&self, size: ComputeNoiseSize) -> bool {
+        (self.runs_on_fn)(self.noise_data.as_ref(), size)
     }
 
     fn needs_uniform(&self) -> bool {
@@ -56,10 +88,16 @@ impl ErasedComputeNoise {
 
 impl<T: ComputeNoise> From<T> for ErasedComputeNoise {
     fn from(value: T) -> Self {
+        let variant = value.pipeline_variant();
         Self {
             noise_data: Box::new(value.clone()),
-            buffers_fn: Box::new(move |render_device| value.buffers(render_device)),
+            buffers_fn: Box::new(move |pool, size| value.buffers(pool, size)),
+            runs_on_fn: |noise, size| noise.downcast_ref::<T>().is_some_and(|noise| noise.runs_on(size)),
             type_id: TypeId::of::<T>(),
+            type_name: T::type_path(),
+            variant,
+            supports_region: T::SUPPORTS_REGION,
+            supports_3d: T::SUPPORTS_3D,
 
             struct_name: T::struct_name(),
             function_name: T::function_name(),
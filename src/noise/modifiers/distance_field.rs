@@ -0,0 +1,169 @@
+use bevy::{app::App, asset::embedded_asset, prelude::*, render::render_resource::{binding_types::{storage_buffer_read_only_sized, storage_buffer_sized, uniform_buffer_sized}, BindGroupLayoutEntryBuilder, Buffer, ShaderRef}};
+use bytemuck::{Pod, Zeroable};
+use serde::{Deserialize, Serialize};
+
+use crate::{buffer_pool::ComputeNoiseBufferPool, image::ComputeNoiseSize, noise::ComputeNoise, noise_queue::{ComputeNoiseSequence, IntoNoiseSequence}, render::pipeline::NoiseOp, shader::ComputeNoiseShader};
+
+use super::{ComputeNoiseModifier, PingPongBuffers};
+
+/// Turns a thresholded mask into a signed distance field using jump flooding.
+///
+/// Distances are measured in texels to the edge of the mask, negative inside and positive outside,
+/// divided by `max_distance` and remapped from -1..1 to 0..1 so they fit in a unorm texture.
+/// 3D textures are limited to 1024 texels per axis.
+#[derive(Clone, Copy, Reflect, PartialEq, Debug, Serialize, Deserialize)]
+#[reflect(Default, Serialize, Deserialize)]
+pub struct DistanceField {
+    pub threshold: f32,
+    pub max_distance: f32,
+    pub flags: DistanceFieldFlags,
+}
+
+#[derive(Clone, Copy, Reflect, PartialEq, Eq, Hash, Debug, Default, Pod, Zeroable, Serialize, Deserialize)]
+#[reflect(Default, Debug, PartialEq, Hash, Serialize, Deserialize)]
+#[serde(transparent)]
+#[repr(transparent)]
+pub struct DistanceFieldFlags(u32);
+
+bitflags::bitflags! {
+    impl DistanceFieldFlags: u32 {
+        const TILEABLE = 1 << 0;
+    }
+}
+
+impl Default for DistanceField {
+    fn default() -> Self {
+        Self {
+            threshold: 0.5,
+            max_distance: 32.0,
+            flags: DistanceFieldFlags::empty(),
+        }
+    }
+}
+
+impl IntoNoiseSequence for DistanceField {
+    fn into_sequence(self, output: Handle<Image>) -> ComputeNoiseSequence {
+        let seeds = PingPongBuffers::default();
+        let pass = |stage: DistanceFieldStage, step: u32, source: usize| DistanceFieldPass {
+            uniform: DistanceFieldUniform {
+                stage: stage as u32,
+                step,
+                flags: self.flags.bits(),
+                threshold: self.threshold,
+                max_distance: self.max_distance,
+                ..default()
+            },
+            source,
+            seeds: seeds.clone(),
+        };
+
+        // Anything further than max_distance is clamped, so the flood never needs to jump further than that.
+        // The extra step of 1 at the end cleans up most of the errors left by jump flooding.
+        let mut steps = Vec::new();
+        let mut step = (self.max_distance.max(1.0).ceil() as u32).next_power_of_two();
+        while step > 0 {
+            steps.push(step);
+            step /= 2;
+        }
+        steps.push(1);
+
+        let mut sequence = pass(DistanceFieldStage::Seed, 0, 1).into_sequence(output.clone());
+        for (i, step) in steps.iter().enumerate() {
+            sequence.0.extend(pass(DistanceFieldStage::Flood, *step, i % 2).into_sequence(output.clone()).0);
+        }
+        sequence.0.extend(pass(DistanceFieldStage::Store, 0, steps.len() % 2).into_sequence(output).0);
+
+        sequence
+    }
+}
+
+#[derive(Clone, Copy)]
+enum DistanceFieldStage {
+    Seed,
+    Flood,
+    Store,
+}
+
+#[derive(Clone, Copy, Reflect, Default, Pod, Zeroable)]
+#[repr(C)]
+struct DistanceFieldUniform {
+    stage: u32,
+    step: u32,
+    flags: u32,
+    threshold: f32,
+    max_distance: f32,
+    _padding: [u32; 3],
+}
+
+/// A single dispatch of the jump flood, the nearest seed of every texel lives in [`PingPongBuffers`].
+#[derive(Clone, Reflect, Default)]
+pub(crate) struct DistanceFieldPass {
+    uniform: DistanceFieldUniform,
+    source: usize,
+    #[reflect(ignore)]
+    seeds: PingPongBuffers,
+}
+
+impl ComputeNoise for DistanceFieldPass {
+    const NOISE_OP: NoiseOp = NoiseOp::Modifier;
+    const SUPPORTS_REGION: bool = false;
+
+    fn buffers(&self, pool: &mut ComputeNoiseBufferPool, size: ComputeNoiseSize) -> Vec<Buffer> {
+        let [source, target] = self.seeds.get(
+            pool.render_device(),
+            "distance_field_seed_buffer",
+            (size.data_len() * std::mem::size_of::<u32>()) as u64,
+            self.source,
+        );
+
+        vec![
+            pool.uniform("distance_field_buffer", bytemuck::cast_slice(&[self.uniform])),
+            source,
+            target,
+        ]
+    }
+}
+
+impl ComputeNoiseModifier for DistanceFieldPass {
+    fn embed_shaders(app: &mut App) {
+        embedded_asset!(app, "shaders/distance_field.wgsl");
+    }
+
+    fn shader() -> ShaderRef {
+        "embedded://bevy_compute_noise/noise/modifiers/shaders/distance_field.wgsl".into()
+    }
+
+    fn buffer_layout() -> Vec<BindGroupLayoutEntryBuilder> {
+        vec![
+            uniform_buffer_sized(false, None),
+            storage_buffer_read_only_sized(false, None),
+            storage_buffer_sized(false, None),
+        ]
+    }
+}
+
+impl ComputeNoiseShader for DistanceFieldPass {
+    fn function_name() -> &'static str {
+        "distance_field"
+    }
+
+    fn import_path() -> &'static str {
+        "bevy_compute_noise::distance_field"
+    }
+
+    fn struct_name() -> Option<&'static str> {
+        Some("DistanceField")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sequence_floods_with_halving_steps() {
+        let sequence = DistanceField { max_distance: 5.0, ..default() }.into_sequence(Handle::default());
+        let passes = sequence.passes(|pass: &DistanceFieldPass| (pass.uniform.stage, pass.uniform.step, pass.source));
+        assert_eq!(passes, vec![(0, 0, 1), (1, 8, 0), (1, 4, 1), (1, 2, 0), (1, 1, 1), (1, 1, 0), (2, 0, 1)]);
+    }
+}
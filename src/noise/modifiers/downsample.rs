@@ -0,0 +1,67 @@
+use bevy::{app::App, asset::embedded_asset, prelude::*, render::render_resource::{binding_types::uniform_buffer_sized, BindGroupLayoutEntryBuilder, Buffer, ShaderRef}};
+use bytemuck::{Pod, Zeroable};
+use serde::{Deserialize, Serialize};
+
+use crate::{buffer_pool::ComputeNoiseBufferPool, image::ComputeNoiseSize, noise::ComputeNoise, render::pipeline::NoiseOp, shader::ComputeNoiseShader};
+
+use super::ComputeNoiseModifier;
+
+/// Box filters one mip into the next, queued automatically after sequences that write to images
+/// from [`ComputeNoiseImage::create_mipmapped_image`](crate::image::ComputeNoiseImage::create_mipmapped_image).
+#[derive(Clone, Copy, Reflect, PartialEq, Debug, Default, Pod, Zeroable, Serialize, Deserialize)]
+#[reflect(Default, Serialize, Deserialize)]
+#[repr(C)]
+pub struct Downsample {
+    pub flags: DownsampleFlags,
+}
+
+#[derive(Clone, Copy, Reflect, PartialEq, Eq, Hash, Debug, Default, Pod, Zeroable, Serialize, Deserialize)]
+#[reflect(Default, Debug, PartialEq, Hash, Serialize, Deserialize)]
+#[serde(transparent)]
+#[repr(transparent)]
+pub struct DownsampleFlags(u32);
+
+bitflags::bitflags! {
+    impl DownsampleFlags: u32 {
+        /// Texels past the edge of odd sized mips are read from the opposite edge instead of clamped.
+        const WRAP = 1 << 0;
+    }
+}
+
+impl ComputeNoise for Downsample {
+    const NOISE_OP: NoiseOp = NoiseOp::Modifier;
+
+    fn buffers(&self, pool: &mut ComputeNoiseBufferPool, _size: ComputeNoiseSize) -> Vec<Buffer> {
+        vec![
+            pool.uniform("downsample_buffer", bytemuck::cast_slice(&[*self]))
+        ]
+    }
+}
+
+impl ComputeNoiseModifier for Downsample {
+    fn embed_shaders(app: &mut App) {
+        embedded_asset!(app, "shaders/downsample.wgsl");
+    }
+
+    fn shader() -> ShaderRef {
+        "embedded://bevy_compute_noise/noise/modifiers/shaders/downsample.wgsl".into()
+    }
+
+    fn buffer_layout() -> Vec<BindGroupLayoutEntryBuilder> {
+        vec![uniform_buffer_sized(false, None)]
+    }
+}
+
+impl ComputeNoiseShader for Downsample {
+    fn function_name() -> &'static str {
+        "downsample"
+    }
+
+    fn import_path() -> &'static str {
+        "bevy_compute_noise::downsample"
+    }
+
+    fn struct_name() -> Option<&'static str> {
+        Some("Downsample")
+    }
+}
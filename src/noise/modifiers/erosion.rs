@@ -0,0 +1,236 @@
+use bevy::{app::App, asset::embedded_asset, prelude::*, render::render_resource::{binding_types::{storage_buffer_read_only_sized, storage_buffer_sized, uniform_buffer_sized}, BindGroupLayoutEntryBuilder, Buffer, ShaderRef}};
+use bytemuck::{Pod, Zeroable};
+use serde::{Deserialize, Serialize};
+
+use crate::{buffer_pool::ComputeNoiseBufferPool, image::ComputeNoiseSize, noise::ComputeNoise, noise_queue::{ComputeNoiseSequence, IntoNoiseSequence}, render::pipeline::NoiseOp, shader::ComputeNoiseShader};
+
+use super::{ComputeNoiseModifier, PingPongBuffers};
+
+/// Moves material downhill wherever the slope is steeper than the talus angle.
+/// Only supports 2D heightmaps stored in the red channel.
+#[derive(Clone, Copy, Reflect, PartialEq, Debug, Serialize, Deserialize)]
+#[reflect(Default, Serialize, Deserialize)]
+pub struct ThermalErosion {
+    pub iterations: u32,
+    /// Spread the simulation over multiple frames, 0 runs every iteration in one frame.
+    pub iterations_per_frame: u32,
+    pub talus: f32,
+    pub strength: f32,
+    pub flags: ErosionFlags,
+}
+
+/// Grid based water simulation that picks up sediment where water flows fast and deposits it where it slows down.
+/// Only supports 2D heightmaps stored in the red channel.
+#[derive(Clone, Copy, Reflect, PartialEq, Debug, Serialize, Deserialize)]
+#[reflect(Default, Serialize, Deserialize)]
+pub struct HydraulicErosion {
+    pub iterations: u32,
+    /// Spread the simulation over multiple frames, 0 runs every iteration in one frame.
+    pub iterations_per_frame: u32,
+    pub rain: f32,
+    pub evaporation: f32,
+    pub capacity: f32,
+    pub solubility: f32,
+    pub deposition: f32,
+    pub flags: ErosionFlags,
+}
+
+#[derive(Clone, Copy, Reflect, PartialEq, Eq, Hash, Debug, Default, Pod, Zeroable, Serialize, Deserialize)]
+#[reflect(Default, Debug, PartialEq, Hash, Serialize, Deserialize)]
+#[serde(transparent)]
+#[repr(transparent)]
+pub struct ErosionFlags(u32);
+
+bitflags::bitflags! {
+    impl ErosionFlags: u32 {
+        const TILEABLE = 1 << 0;
+    }
+}
+
+impl Default for ThermalErosion {
+    fn default() -> Self {
+        Self {
+            iterations: 50,
+            iterations_per_frame: 0,
+            talus: 0.004,
+            strength: 0.5,
+            flags: ErosionFlags::empty(),
+        }
+    }
+}
+
+impl Default for HydraulicErosion {
+    fn default() -> Self {
+        Self {
+            iterations: 100,
+            iterations_per_frame: 0,
+            rain: 0.001,
+            evaporation: 0.05,
+            capacity: 1.0,
+            solubility: 0.1,
+            deposition: 0.1,
+            flags: ErosionFlags::empty(),
+        }
+    }
+}
+
+impl IntoNoiseSequence for ThermalErosion {
+    fn into_sequence(self, output: Handle<Image>) -> ComputeNoiseSequence {
+        erosion_sequence(
+            ErosionUniform {
+                model: ErosionModel::Thermal as u32,
+                flags: self.flags.bits(),
+                talus: self.talus,
+                strength: self.strength,
+                ..default()
+            },
+            self.iterations,
+            self.iterations_per_frame,
+            output,
+        )
+    }
+}
+
+impl IntoNoiseSequence for HydraulicErosion {
+    fn into_sequence(self, output: Handle<Image>) -> ComputeNoiseSequence {
+        erosion_sequence(
+            ErosionUniform {
+                model: ErosionModel::Hydraulic as u32,
+                flags: self.flags.bits(),
+                rain: self.rain,
+                evaporation: self.evaporation,
+                capacity: self.capacity,
+                solubility: self.solubility,
+                deposition: self.deposition,
+                ..default()
+            },
+            self.iterations,
+            self.iterations_per_frame,
+            output,
+        )
+    }
+}
+
+fn erosion_sequence(uniform: ErosionUniform, iterations: u32, iterations_per_frame: u32, output: Handle<Image>) -> ComputeNoiseSequence {
+    let state = PingPongBuffers::default();
+    let pass = |stage: ErosionStage, source: usize| ErosionPass {
+        uniform: ErosionUniform { stage: stage as u32, ..uniform },
+        source,
+        state: state.clone(),
+    };
+
+    let mut sequence = pass(ErosionStage::Load, 1).into_sequence(output.clone());
+    for i in 0..iterations as usize {
+        let mut step = pass(ErosionStage::Step, i % 2).into_sequence(output.clone());
+        if iterations_per_frame != 0 && (i + 1) % iterations_per_frame as usize == 0 {
+            step.0[0].frame_break = true;
+        }
+        sequence.0.extend(step.0);
+    }
+    sequence.0.extend(pass(ErosionStage::Store, iterations as usize % 2).into_sequence(output).0);
+
+    sequence
+}
+
+#[derive(Clone, Copy)]
+enum ErosionModel {
+    Thermal,
+    Hydraulic,
+}
+
+#[derive(Clone, Copy)]
+enum ErosionStage {
+    Load,
+    Step,
+    Store,
+}
+
+#[derive(Clone, Copy, Reflect, Default, Pod, Zeroable)]
+#[repr(C)]
+struct ErosionUniform {
+    model: u32,
+    stage: u32,
+    flags: u32,
+    talus: f32,
+    strength: f32,
+    rain: f32,
+    evaporation: f32,
+    capacity: f32,
+    solubility: f32,
+    deposition: f32,
+    _padding: [u32; 2],
+}
+
+/// A single dispatch of an erosion simulation, the cell state lives in [`PingPongBuffers`].
+#[derive(Clone, Reflect, Default)]
+pub(crate) struct ErosionPass {
+    uniform: ErosionUniform,
+    source: usize,
+    #[reflect(ignore)]
+    state: PingPongBuffers,
+}
+
+impl ComputeNoise for ErosionPass {
+    const NOISE_OP: NoiseOp = NoiseOp::Modifier;
+    const SUPPORTS_REGION: bool = false;
+    const SUPPORTS_3D: bool = false;
+
+    fn buffers(&self, pool: &mut ComputeNoiseBufferPool, size: ComputeNoiseSize) -> Vec<Buffer> {
+        let [source, target] = self.state.get(
+            pool.render_device(),
+            "erosion_state_buffer",
+            (size.data_len() * std::mem::size_of::<[f32; 4]>()) as u64,
+            self.source,
+        );
+
+        vec![
+            pool.uniform("erosion_buffer", bytemuck::cast_slice(&[self.uniform])),
+            source,
+            target,
+        ]
+    }
+}
+
+impl ComputeNoiseModifier for ErosionPass {
+    fn embed_shaders(app: &mut App) {
+        embedded_asset!(app, "shaders/erosion.wgsl");
+    }
+
+    fn shader() -> ShaderRef {
+        "embedded://bevy_compute_noise/noise/modifiers/shaders/erosion.wgsl".into()
+    }
+
+    fn buffer_layout() -> Vec<BindGroupLayoutEntryBuilder> {
+        vec![
+            uniform_buffer_sized(false, None),
+            storage_buffer_read_only_sized(false, None),
+            storage_buffer_sized(false, None),
+        ]
+    }
+}
+
+impl ComputeNoiseShader for ErosionPass {
+    fn function_name() -> &'static str {
+        "erosion"
+    }
+
+    fn import_path() -> &'static str {
+        "bevy_compute_noise::erosion"
+    }
+
+    fn struct_name() -> Option<&'static str> {
+        Some("Erosion")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sequence_steps_between_load_and_store() {
+        let sequence = ThermalErosion { iterations: 3, iterations_per_frame: 2, ..default() }.into_sequence(Handle::default());
+        assert_eq!(sequence.passes(|pass: &ErosionPass| (pass.uniform.stage, pass.source)), vec![(0, 1), (1, 0), (1, 1), (1, 0), (2, 1)]);
+        assert_eq!(sequence.frame_breaks(), vec![false, false, true, false, false]);
+    }
+}
@@ -0,0 +1,110 @@
+use bevy::{app::App, asset::embedded_asset, prelude::*, render::render_resource::{binding_types::{storage_buffer_read_only_sized, uniform_buffer_sized}, BindGroupLayoutEntryBuilder, Buffer, ShaderRef}};
+use bytemuck::{Pod, Zeroable};
+use serde::{Deserialize, Serialize};
+
+use crate::{buffer_pool::ComputeNoiseBufferPool, image::ComputeNoiseSize, noise::ComputeNoise, render::pipeline::NoiseOp, shader::ComputeNoiseShader};
+
+use super::ComputeNoiseModifier;
+
+/// Maps the value in the red channel through a colour gradient and writes the colour to RGBA.
+/// Stops are `(position, colour)` pairs and don't need to be sorted.
+#[derive(Clone, Reflect, PartialEq, Debug, Serialize, Deserialize)]
+#[reflect(Default, Serialize, Deserialize)]
+pub struct GradientMap {
+    pub stops: Vec<(f32, Color)>,
+    pub interpolation: GradientInterpolation,
+}
+
+#[derive(Clone, Copy, Reflect, Default, PartialEq, Eq, Debug, Serialize, Deserialize)]
+#[reflect(Default, Serialize, Deserialize)]
+pub enum GradientInterpolation {
+    #[default]
+    Linear,
+    Constant,
+    SmoothStep,
+}
+
+impl Default for GradientMap {
+    fn default() -> Self {
+        Self {
+            stops: vec![(0.0, Color::BLACK), (1.0, Color::WHITE)],
+            interpolation: GradientInterpolation::default(),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Pod, Zeroable)]
+#[repr(C)]
+struct GradientMapUniform {
+    stop_count: u32,
+    interpolation: u32,
+    _padding: [u32; 2],
+}
+
+#[derive(Clone, Copy, Pod, Zeroable)]
+#[repr(C)]
+struct GradientStop {
+    color: [f32; 4],
+    position: f32,
+    _padding: [f32; 3],
+}
+
+impl ComputeNoise for GradientMap {
+    const NOISE_OP: NoiseOp = NoiseOp::Modifier;
+
+    fn buffers(&self, pool: &mut ComputeNoiseBufferPool, _size: ComputeNoiseSize) -> Vec<Buffer> {
+        let mut stops: Vec<GradientStop> = self.stops.iter()
+            .map(|(position, color)| GradientStop {
+                color: color.to_linear().to_f32_array(),
+                position: *position,
+                _padding: [0.0; 3],
+            })
+            .collect();
+        stops.sort_by(|a, b| a.position.total_cmp(&b.position));
+
+        // Storage buffers can't be empty.
+        if stops.is_empty() {
+            stops.push(GradientStop::zeroed());
+        }
+
+        vec![
+            pool.uniform("gradient_map_buffer", bytemuck::cast_slice(&[GradientMapUniform {
+                stop_count: stops.len() as u32,
+                interpolation: self.interpolation as u32,
+                _padding: [0; 2],
+            }])),
+            pool.storage("gradient_map_stops_buffer", bytemuck::cast_slice(&stops)),
+        ]
+    }
+}
+
+impl ComputeNoiseModifier for GradientMap {
+    fn embed_shaders(app: &mut App) {
+        embedded_asset!(app, "shaders/gradient_map.wgsl");
+    }
+
+    fn shader() -> ShaderRef {
+        "embedded://bevy_compute_noise/noise/modifiers/shaders/gradient_map.wgsl".into()
+    }
+
+    fn buffer_layout() -> Vec<BindGroupLayoutEntryBuilder> {
+        vec![
+            uniform_buffer_sized(false, None),
+            storage_buffer_read_only_sized(false, None),
+        ]
+    }
+}
+
+impl ComputeNoiseShader for GradientMap {
+    fn function_name() -> &'static str {
+        "gradient_map"
+    }
+
+    fn import_path() -> &'static str {
+        "bevy_compute_noise::gradient_map"
+    }
+
+    fn struct_name() -> Option<&'static str> {
+        Some("GradientMap")
+    }
+}
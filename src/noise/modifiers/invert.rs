@@ -1,16 +1,18 @@
-use bevy::{app::App, asset::embedded_asset, reflect::Reflect, render::{render_resource::{Buffer, ShaderRef}, renderer::RenderDevice}};
+use bevy::{app::App, asset::embedded_asset, prelude::*, render::render_resource::{Buffer, ShaderRef}};
+use serde::{Deserialize, Serialize};
 
-use crate::{noise::ComputeNoise, render::pipeline::NoiseOp, shader::ComputeNoiseShader};
+use crate::{buffer_pool::ComputeNoiseBufferPool, image::ComputeNoiseSize, noise::ComputeNoise, render::pipeline::NoiseOp, shader::ComputeNoiseShader};
 
 use super::ComputeNoiseModifier;
 
-#[derive(Clone, Reflect, Default)]
+#[derive(Clone, Copy, Reflect, Default, PartialEq, Debug, Serialize, Deserialize)]
+#[reflect(Default, Serialize, Deserialize)]
 pub struct Invert;
 
 impl ComputeNoise for Invert {
     const NOISE_OP: NoiseOp = NoiseOp::Modifier; 
 
-    fn buffers(&self, _render_device: &RenderDevice) -> Vec<Buffer> {
+    fn buffers(&self, _pool: &mut ComputeNoiseBufferPool, _size: ComputeNoiseSize) -> Vec<Buffer> {
         Vec::new()
     }
 }
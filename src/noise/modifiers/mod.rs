@@ -1,12 +1,54 @@
-use bevy::{app::App, render::render_resource::ShaderRef};
+use std::sync::{Arc, OnceLock};
+
+use bevy::{app::App, render::{render_resource::{BindGroupLayoutEntryBuilder, Buffer, BufferDescriptor, BufferUsages, ShaderRef}, renderer::RenderDevice}};
 
 use crate::noise::ComputeNoise;
 
 pub mod invert;
+pub mod erosion;
+pub mod distance_field;
+pub mod gradient_map;
+pub mod threshold;
+pub mod morphology;
+pub mod downsample;
 
 pub use invert::Invert;
+pub use erosion::{ThermalErosion, HydraulicErosion, ErosionFlags};
+pub use distance_field::{DistanceField, DistanceFieldFlags};
+pub use gradient_map::{GradientMap, GradientInterpolation};
+pub use threshold::{Threshold, ThresholdFlags, SmoothStep};
+pub use morphology::{Morphology, MorphologyOperation, StructuringElement, MorphologyFlags};
+pub use downsample::{Downsample, DownsampleFlags};
 
 pub trait ComputeNoiseModifier: ComputeNoise {
     fn embed_shaders(app: &mut App);
     fn shader() -> ShaderRef;
-}
\ No newline at end of file
+
+    /// Layout of any buffers bound after the input and output textures.
+    fn buffer_layout() -> Vec<BindGroupLayoutEntryBuilder> {
+        Vec::new()
+    }
+}
+
+/// Pair of storage buffers shared by every pass of multi-pass noise, created the first time they're needed.
+///
+/// Each pass reads the state the previous one wrote and writes the other buffer, so the `source` a pass
+/// reads from alternates through the sequence. Layers of an array texture are stored one after another.
+#[derive(Clone, Default)]
+pub(crate) struct PingPongBuffers(Arc<OnceLock<[Buffer; 2]>>);
+
+impl PingPongBuffers {
+    /// Returns the buffer to read from and the buffer to write to.
+    pub fn get(&self, render_device: &RenderDevice, label: &'static str, size: u64, source: usize) -> [Buffer; 2] {
+        let buffers = self.0.get_or_init(|| {
+            [0, 1].map(|_| render_device.create_buffer(&BufferDescriptor {
+                label: Some(label),
+                size,
+                usage: BufferUsages::STORAGE,
+                mapped_at_creation: false,
+            }))
+        });
+
+        [buffers[source].clone(), buffers[1 - source].clone()]
+    }
+}
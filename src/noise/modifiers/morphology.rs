@@ -0,0 +1,198 @@
+use bevy::{app::App, asset::embedded_asset, prelude::*, render::render_resource::{binding_types::{storage_buffer_read_only_sized, storage_buffer_sized, uniform_buffer_sized}, BindGroupLayoutEntryBuilder, Buffer, ShaderRef}};
+use bytemuck::{Pod, Zeroable};
+use serde::{Deserialize, Serialize};
+
+use crate::{buffer_pool::ComputeNoiseBufferPool, image::ComputeNoiseSize, noise::ComputeNoise, noise_queue::{ComputeNoiseSequence, IntoNoiseSequence}, render::pipeline::NoiseOp, shader::ComputeNoiseShader};
+
+use super::{ComputeNoiseModifier, PingPongBuffers};
+
+/// Grayscale morphology on the red channel, useful for cleaning up masks.
+#[derive(Clone, Copy, Reflect, PartialEq, Debug, Serialize, Deserialize)]
+#[reflect(Default, Serialize, Deserialize)]
+pub struct Morphology {
+    pub operation: MorphologyOperation,
+    /// Radius of the structuring element in texels.
+    pub radius: u32,
+    pub element: StructuringElement,
+    pub flags: MorphologyFlags,
+}
+
+#[derive(Clone, Copy, Reflect, Default, PartialEq, Eq, Debug, Serialize, Deserialize)]
+#[reflect(Default, Serialize, Deserialize)]
+pub enum MorphologyOperation {
+    /// Maximum over the structuring element.
+    #[default]
+    Dilate,
+    /// Minimum over the structuring element.
+    Erode,
+    /// Erode followed by dilate, removes small bright features.
+    Open,
+    /// Dilate followed by erode, fills small dark gaps.
+    Close,
+}
+
+#[derive(Clone, Copy, Reflect, Default, PartialEq, Eq, Debug, Serialize, Deserialize)]
+#[reflect(Default, Serialize, Deserialize)]
+pub enum StructuringElement {
+    /// A square in 2D and a cube in 3D.
+    #[default]
+    Square,
+    /// A disc in the XY plane, in 3D every slice is processed on its own.
+    Disc,
+    /// A sphere in 3D, the same as `Disc` in 2D.
+    Sphere,
+}
+
+#[derive(Clone, Copy, Reflect, PartialEq, Eq, Hash, Debug, Default, Pod, Zeroable, Serialize, Deserialize)]
+#[reflect(Default, Debug, PartialEq, Hash, Serialize, Deserialize)]
+#[serde(transparent)]
+#[repr(transparent)]
+pub struct MorphologyFlags(u32);
+
+bitflags::bitflags! {
+    impl MorphologyFlags: u32 {
+        const TILEABLE = 1 << 0;
+    }
+}
+
+impl Default for Morphology {
+    fn default() -> Self {
+        Self {
+            operation: MorphologyOperation::default(),
+            radius: 1,
+            element: StructuringElement::default(),
+            flags: MorphologyFlags::empty(),
+        }
+    }
+}
+
+impl IntoNoiseSequence for Morphology {
+    fn into_sequence(self, output: Handle<Image>) -> ComputeNoiseSequence {
+        let values = PingPongBuffers::default();
+        let pass = |stage: MorphologyStage, operation: MorphologyOperation, source: usize| MorphologyPass {
+            uniform: MorphologyUniform {
+                stage: stage as u32,
+                operation: operation as u32,
+                radius: self.radius,
+                element: self.element as u32,
+                flags: self.flags.bits(),
+                ..default()
+            },
+            source,
+            values: values.clone(),
+        };
+
+        let operations = match self.operation {
+            MorphologyOperation::Dilate => vec![MorphologyOperation::Dilate],
+            MorphologyOperation::Erode => vec![MorphologyOperation::Erode],
+            MorphologyOperation::Open => vec![MorphologyOperation::Erode, MorphologyOperation::Dilate],
+            MorphologyOperation::Close => vec![MorphologyOperation::Dilate, MorphologyOperation::Erode],
+        };
+
+        // Neighbours are read from a copy of the texture so the output can be written in place.
+        let mut sequence = pass(MorphologyStage::Load, self.operation, 1).into_sequence(output.clone());
+        for (i, operation) in operations.iter().enumerate() {
+            let stage = if i == operations.len() - 1 { MorphologyStage::Store } else { MorphologyStage::Filter };
+            sequence.0.extend(pass(stage, *operation, i % 2).into_sequence(output.clone()).0);
+        }
+
+        sequence
+    }
+}
+
+#[derive(Clone, Copy)]
+enum MorphologyStage {
+    Load,
+    Filter,
+    Store,
+}
+
+#[derive(Clone, Copy, Reflect, Default, Pod, Zeroable)]
+#[repr(C)]
+struct MorphologyUniform {
+    stage: u32,
+    operation: u32,
+    radius: u32,
+    element: u32,
+    flags: u32,
+    _padding: [u32; 3],
+}
+
+/// A single dispatch of a morphology operation, the texel values live in [`PingPongBuffers`].
+#[derive(Clone, Reflect, Default)]
+pub(crate) struct MorphologyPass {
+    uniform: MorphologyUniform,
+    source: usize,
+    #[reflect(ignore)]
+    values: PingPongBuffers,
+}
+
+impl ComputeNoise for MorphologyPass {
+    const NOISE_OP: NoiseOp = NoiseOp::Modifier;
+    const SUPPORTS_REGION: bool = false;
+
+    fn buffers(&self, pool: &mut ComputeNoiseBufferPool, size: ComputeNoiseSize) -> Vec<Buffer> {
+        let [source, target] = self.values.get(
+            pool.render_device(),
+            "morphology_value_buffer",
+            (size.data_len() * std::mem::size_of::<f32>()) as u64,
+            self.source,
+        );
+
+        vec![
+            pool.uniform("morphology_buffer", bytemuck::cast_slice(&[self.uniform])),
+            source,
+            target,
+        ]
+    }
+}
+
+impl ComputeNoiseModifier for MorphologyPass {
+    fn embed_shaders(app: &mut App) {
+        embedded_asset!(app, "shaders/morphology.wgsl");
+    }
+
+    fn shader() -> ShaderRef {
+        "embedded://bevy_compute_noise/noise/modifiers/shaders/morphology.wgsl".into()
+    }
+
+    fn buffer_layout() -> Vec<BindGroupLayoutEntryBuilder> {
+        vec![
+            uniform_buffer_sized(false, None),
+            storage_buffer_read_only_sized(false, None),
+            storage_buffer_sized(false, None),
+        ]
+    }
+}
+
+impl ComputeNoiseShader for MorphologyPass {
+    fn function_name() -> &'static str {
+        "morphology"
+    }
+
+    fn import_path() -> &'static str {
+        "bevy_compute_noise::morphology"
+    }
+
+    fn struct_name() -> Option<&'static str> {
+        Some("Morphology")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sequence_chains_operations() {
+        let passes = |operation| {
+            Morphology { operation, ..default() }.into_sequence(Handle::default())
+                .passes(|pass: &MorphologyPass| (pass.uniform.stage, pass.uniform.operation, pass.source))
+        };
+
+        let (dilate, erode) = (MorphologyOperation::Dilate as u32, MorphologyOperation::Erode as u32);
+        assert_eq!(passes(MorphologyOperation::Erode), vec![(0, erode, 1), (2, erode, 0)]);
+        assert_eq!(passes(MorphologyOperation::Open), vec![(0, MorphologyOperation::Open as u32, 1), (1, erode, 0), (2, dilate, 1)]);
+        assert_eq!(passes(MorphologyOperation::Close), vec![(0, MorphologyOperation::Close as u32, 1), (1, dilate, 0), (2, erode, 1)]);
+    }
+}
@@ -0,0 +1,187 @@
+#import bevy_compute_noise::dispatch::{dispatch_location, outside_dispatch, dispatch_layer, cell_index}
+
+const TILEABLE: u32 = 1u;
+
+const STAGE_SEED: u32 = 0u;
+const STAGE_FLOOD: u32 = 1u;
+const STAGE_STORE: u32 = 2u;
+
+const NO_SEED: u32 = 0xffffffffu;
+const INFINITY = 3.402823e+38;
+
+struct DistanceField {
+    stage: u32,
+    step: u32,
+    flags: u32,
+    threshold: f32,
+    max_distance: f32,
+}
+
+#ifdef ARRAY
+    @group(0) @binding(0) var input_texture: texture_storage_2d_array<rgba8unorm, read_write>;
+    @group(0) @binding(1) var output_texture: texture_storage_2d_array<rgba8unorm, read_write>;
+#else ifdef 2D
+    @group(0) @binding(0) var input_texture: texture_storage_2d<rgba8unorm, read_write>;
+    @group(0) @binding(1) var output_texture: texture_storage_2d<rgba8unorm, read_write>;
+#endif
+#ifdef 3D
+    @group(0) @binding(0) var input_texture: texture_storage_3d<rgba8unorm, read_write>;
+    @group(0) @binding(1) var output_texture: texture_storage_3d<rgba8unorm, read_write>;
+#endif
+@group(0) @binding(2) var<uniform> distance_field: DistanceField;
+@group(0) @binding(3) var<storage, read> seeds_in: array<u32>;
+@group(0) @binding(4) var<storage, read_write> seeds_out: array<u32>;
+
+#ifdef 2D
+    @compute @workgroup_size(16, 16)
+#endif
+#ifdef 3D
+    @compute @workgroup_size(8, 8, 4)
+#endif
+fn main(@builtin(global_invocation_id) invocation_id: vec3<u32>) {
+    #ifdef 2D
+        let size = vec3<i32>(vec2<i32>(textureDimensions(output_texture)), 1);
+    #endif
+    #ifdef 3D
+        let size = vec3<i32>(textureDimensions(output_texture));
+    #endif
+    let texel = dispatch_location(invocation_id);
+    if outside_dispatch(texel) { return; }
+    let location = vec3<i32>(texel);
+
+    let index = cell_index(location, size);
+    switch distance_field.stage {
+        case STAGE_SEED: {
+            seeds_out[index] = select(NO_SEED, pack(location), is_edge(location, size));
+        }
+        case STAGE_FLOOD: {
+            seeds_out[index] = flood(location, size);
+        }
+        case STAGE_STORE: {
+            let seed = seeds_in[index];
+            var distance = distance_field.max_distance;
+            if seed != NO_SEED {
+                distance = min(seed_distance(location, unpack(seed), size), distance_field.max_distance);
+            }
+
+            let sign = select(1.0, -1.0, is_inside(location));
+            let value = clamp(sign * distance / distance_field.max_distance * 0.5 + 0.5, 0.0, 1.0);
+            store(location, value);
+        }
+        default: {}
+    }
+}
+
+fn load(location: vec3<i32>) -> f32 {
+    #ifdef ARRAY
+        return textureLoad(input_texture, location.xy, dispatch_layer()).r;
+    #else ifdef 2D
+        return textureLoad(input_texture, location.xy).r;
+    #endif
+    #ifdef 3D
+        return textureLoad(input_texture, location).r;
+    #endif
+}
+
+fn store(location: vec3<i32>, value: f32) {
+    #ifdef ARRAY
+        textureStore(output_texture, location.xy, dispatch_layer(), vec4<f32>(value, 0.0, 0.0, 1.0));
+    #else ifdef 2D
+        textureStore(output_texture, location.xy, vec4<f32>(value, 0.0, 0.0, 1.0));
+    #endif
+    #ifdef 3D
+        textureStore(output_texture, location, vec4<f32>(value, 0.0, 0.0, 1.0));
+    #endif
+}
+
+fn pack(location: vec3<i32>) -> u32 {
+    let p = vec3<u32>(location);
+    #ifdef 2D
+        return p.x | (p.y << 16u);
+    #endif
+    #ifdef 3D
+        return p.x | (p.y << 10u) | (p.z << 20u);
+    #endif
+}
+
+fn unpack(seed: u32) -> vec3<i32> {
+    #ifdef 2D
+        return vec3<i32>(vec3<u32>(seed & 0xffffu, seed >> 16u, 0u));
+    #endif
+    #ifdef 3D
+        return vec3<i32>(vec3<u32>(seed & 0x3ffu, (seed >> 10u) & 0x3ffu, seed >> 20u));
+    #endif
+}
+
+fn is_tileable() -> bool {
+    return (distance_field.flags & TILEABLE) != 0u;
+}
+
+fn in_bounds(location: vec3<i32>, size: vec3<i32>) -> bool {
+    return all(location >= vec3<i32>(0)) && all(location < size);
+}
+
+fn wrap(location: vec3<i32>, size: vec3<i32>) -> vec3<i32> {
+    return ((location % size) + size) % size;
+}
+
+fn is_inside(location: vec3<i32>) -> bool {
+    return load(location) >= distance_field.threshold;
+}
+
+// A texel is a seed when any of its direct neighbours is on the other side of the threshold.
+fn is_edge(location: vec3<i32>, size: vec3<i32>) -> bool {
+    let inside = is_inside(location);
+    for (var axis = 0; axis < 3; axis++) {
+        for (var direction = -1; direction <= 1; direction += 2) {
+            var offset = vec3<i32>(0);
+            offset[axis] = direction;
+
+            var neighbour = location + offset;
+            if is_tileable() {
+                neighbour = wrap(neighbour, size);
+            } else if !in_bounds(neighbour, size) {
+                continue;
+            }
+
+            if is_inside(neighbour) != inside { return true; }
+        }
+    }
+    return false;
+}
+
+fn seed_distance(location: vec3<i32>, seed: vec3<i32>, size: vec3<i32>) -> f32 {
+    var difference = abs(location - seed);
+    if is_tileable() { difference = min(difference, size - difference); }
+    return length(vec3<f32>(difference));
+}
+
+fn flood(location: vec3<i32>, size: vec3<i32>) -> u32 {
+    let step = i32(distance_field.step);
+    let depth = select(1, 0, size.z == 1);
+
+    var best_seed = NO_SEED;
+    var best_distance = INFINITY;
+    for (var z = -depth; z <= depth; z++) {
+        for (var y = -1; y <= 1; y++) {
+            for (var x = -1; x <= 1; x++) {
+                var neighbour = location + vec3<i32>(x, y, z) * step;
+                if is_tileable() {
+                    neighbour = wrap(neighbour, size);
+                } else if !in_bounds(neighbour, size) {
+                    continue;
+                }
+
+                let seed = seeds_in[cell_index(neighbour, size)];
+                if seed == NO_SEED { continue; }
+
+                let distance = seed_distance(location, unpack(seed), size);
+                if distance < best_distance {
+                    best_distance = distance;
+                    best_seed = seed;
+                }
+            }
+        }
+    }
+    return best_seed;
+}
@@ -0,0 +1,74 @@
+#import bevy_compute_noise::dispatch::{dispatch_location, outside_dispatch, dispatch_layer}
+
+const WRAP: u32 = 1u;
+
+struct Downsample {
+    flags: u32,
+}
+
+// The input is the previous mip and the output the mip being written.
+#ifdef ARRAY
+    @group(0) @binding(0) var input_texture: texture_storage_2d_array<rgba8unorm, read_write>;
+    @group(0) @binding(1) var output_texture: texture_storage_2d_array<rgba8unorm, read_write>;
+#else ifdef 2D
+    @group(0) @binding(0) var input_texture: texture_storage_2d<rgba8unorm, read_write>;
+    @group(0) @binding(1) var output_texture: texture_storage_2d<rgba8unorm, read_write>;
+#endif
+#ifdef 3D
+    @group(0) @binding(0) var input_texture: texture_storage_3d<rgba8unorm, read_write>;
+    @group(0) @binding(1) var output_texture: texture_storage_3d<rgba8unorm, read_write>;
+#endif
+@group(0) @binding(2) var<uniform> config: Downsample;
+
+#ifdef 2D
+    @compute @workgroup_size(16, 16)
+#endif
+#ifdef 3D
+    @compute @workgroup_size(8, 8, 4)
+#endif
+fn main(@builtin(global_invocation_id) invocation_id: vec3<u32>) {
+    let texel = dispatch_location(invocation_id);
+    if outside_dispatch(texel) { return; }
+
+    var value = vec4<f32>(0.0);
+    #ifdef 2D
+        let location = texel.xy;
+        for (var i = 0u; i < 4u; i++) {
+            let offset = vec2<u32>(i & 1u, i >> 1u);
+            #ifdef ARRAY
+                value += textureLoad(input_texture, source_location(location * 2u + offset), dispatch_layer());
+            #else
+                value += textureLoad(input_texture, source_location(location * 2u + offset));
+            #endif
+        }
+        value /= 4.0;
+    #endif
+    #ifdef 3D
+        let location = texel;
+        for (var i = 0u; i < 8u; i++) {
+            let offset = vec3<u32>(i & 1u, (i >> 1u) & 1u, i >> 2u);
+            value += textureLoad(input_texture, source_location(location * 2u + offset));
+        }
+        value /= 8.0;
+    #endif
+
+    #ifdef ARRAY
+        textureStore(output_texture, location, dispatch_layer(), value);
+    #else
+        textureStore(output_texture, location, value);
+    #endif
+}
+
+// Only odd sized mips and 1 texel wide axes read past the edge.
+#ifdef 2D
+    fn source_location(location: vec2<u32>) -> vec2<u32> {
+        let size = textureDimensions(input_texture);
+        return select(min(location, size - 1u), location % size, (config.flags & WRAP) != 0u);
+    }
+#endif
+#ifdef 3D
+    fn source_location(location: vec3<u32>) -> vec3<u32> {
+        let size = textureDimensions(input_texture);
+        return select(min(location, size - 1u), location % size, (config.flags & WRAP) != 0u);
+    }
+#endif
@@ -0,0 +1,180 @@
+#import bevy_compute_noise::dispatch::{dispatch_location, outside_dispatch, dispatch_layer, layer_cell_index}
+
+const TILEABLE: u32 = 1u;
+
+const THERMAL: u32 = 0u;
+
+const STAGE_LOAD: u32 = 0u;
+const STAGE_STEP: u32 = 1u;
+const STAGE_STORE: u32 = 2u;
+
+struct Erosion {
+    model: u32,
+    stage: u32,
+    flags: u32,
+    talus: f32,
+    strength: f32,
+    rain: f32,
+    evaporation: f32,
+    capacity: f32,
+    solubility: f32,
+    deposition: f32,
+}
+
+#ifdef ARRAY
+@group(0) @binding(0) var input_texture: texture_storage_2d_array<rgba8unorm, read_write>;
+@group(0) @binding(1) var output_texture: texture_storage_2d_array<rgba8unorm, read_write>;
+#else
+@group(0) @binding(0) var input_texture: texture_storage_2d<rgba8unorm, read_write>;
+@group(0) @binding(1) var output_texture: texture_storage_2d<rgba8unorm, read_write>;
+#endif
+@group(0) @binding(2) var<uniform> erosion: Erosion;
+// x: height, y: water, z: sediment
+@group(0) @binding(3) var<storage, read> cells_in: array<vec4<f32>>;
+@group(0) @binding(4) var<storage, read_write> cells_out: array<vec4<f32>>;
+
+@compute @workgroup_size(16, 16)
+fn main(@builtin(global_invocation_id) invocation_id: vec3<u32>) {
+    let size = vec2<i32>(textureDimensions(output_texture));
+    let texel = dispatch_location(invocation_id);
+    if outside_dispatch(texel) { return; }
+    let location = vec2<i32>(texel.xy);
+
+    let index = layer_cell_index(location, i32(dispatch_layer()), size);
+    switch erosion.stage {
+        case STAGE_LOAD: {
+            #ifdef ARRAY
+                cells_out[index] = vec4<f32>(textureLoad(input_texture, location, dispatch_layer()).r, 0.0, 0.0, 0.0);
+            #else
+                cells_out[index] = vec4<f32>(textureLoad(input_texture, location).r, 0.0, 0.0, 0.0);
+            #endif
+        }
+        case STAGE_STEP: {
+            if erosion.model == THERMAL {
+                cells_out[index] = thermal_step(location, size);
+            } else {
+                cells_out[index] = hydraulic_step(location, size);
+            }
+        }
+        case STAGE_STORE: {
+            let cell = cells_in[index];
+            let value = clamp(cell.x + cell.z, 0.0, 1.0);
+            #ifdef ARRAY
+                textureStore(output_texture, location, dispatch_layer(), vec4<f32>(value, 0.0, 0.0, 1.0));
+            #else
+                textureStore(output_texture, location, vec4<f32>(value, 0.0, 0.0, 1.0));
+            #endif
+        }
+        default: {}
+    }
+}
+
+fn offset(direction: u32) -> vec2<i32> {
+    var offsets = array<vec2<i32>, 4>(
+        vec2<i32>(1, 0),
+        vec2<i32>(-1, 0),
+        vec2<i32>(0, 1),
+        vec2<i32>(0, -1),
+    );
+    return offsets[direction];
+}
+
+// Returns -1 when the neighbour is outside a non-tileable texture.
+fn neighbour_index(location: vec2<i32>, direction: u32, size: vec2<i32>) -> i32 {
+    let neighbour = location + offset(direction);
+    if (erosion.flags & TILEABLE) != 0u {
+        return layer_cell_index((neighbour + size) % size, i32(dispatch_layer()), size);
+    }
+    if any(neighbour < vec2<i32>(0)) || any(neighbour >= size) { return -1; }
+    return layer_cell_index(neighbour, i32(dispatch_layer()), size);
+}
+
+fn neighbour_location(location: vec2<i32>, direction: u32, size: vec2<i32>) -> vec2<i32> {
+    return (location + offset(direction) + size) % size;
+}
+
+// Material moved from the cell to each of its neighbours this step.
+fn thermal_outflow(location: vec2<i32>, size: vec2<i32>) -> vec4<f32> {
+    let height = cells_in[layer_cell_index(location, i32(dispatch_layer()), size)].x;
+
+    var excess = vec4<f32>(0.0);
+    for (var i = 0u; i < 4u; i++) {
+        let neighbour = neighbour_index(location, i, size);
+        if neighbour < 0 { continue; }
+        excess[i] = max(height - cells_in[neighbour].x - erosion.talus, 0.0);
+    }
+
+    let total = dot(excess, vec4<f32>(1.0));
+    if total <= 0.0 { return vec4<f32>(0.0); }
+
+    let amount = erosion.strength * 0.5 * max(max(excess.x, excess.y), max(excess.z, excess.w));
+    return excess / total * amount;
+}
+
+fn thermal_step(location: vec2<i32>, size: vec2<i32>) -> vec4<f32> {
+    var cell = cells_in[layer_cell_index(location, i32(dispatch_layer()), size)];
+    let outflow = thermal_outflow(location, size);
+
+    var inflow = 0.0;
+    for (var i = 0u; i < 4u; i++) {
+        if neighbour_index(location, i, size) < 0 { continue; }
+        inflow += thermal_outflow(neighbour_location(location, i, size), size)[i ^ 1u];
+    }
+
+    cell.x += inflow - dot(outflow, vec4<f32>(1.0));
+    return cell;
+}
+
+// Water moved from the cell to each of its neighbours this step.
+fn hydraulic_outflow(location: vec2<i32>, size: vec2<i32>) -> vec4<f32> {
+    let cell = cells_in[layer_cell_index(location, i32(dispatch_layer()), size)];
+    let water = cell.y + erosion.rain;
+    let surface = cell.x + water;
+
+    var difference = vec4<f32>(0.0);
+    for (var i = 0u; i < 4u; i++) {
+        let neighbour = neighbour_index(location, i, size);
+        if neighbour < 0 { continue; }
+        let other = cells_in[neighbour];
+        difference[i] = max(surface - (other.x + other.y + erosion.rain), 0.0);
+    }
+
+    let total = dot(difference, vec4<f32>(1.0));
+    if total <= 0.0 { return vec4<f32>(0.0); }
+
+    let amount = min(water, 0.5 * max(max(difference.x, difference.y), max(difference.z, difference.w)));
+    return difference / total * amount;
+}
+
+fn hydraulic_step(location: vec2<i32>, size: vec2<i32>) -> vec4<f32> {
+    let cell = cells_in[layer_cell_index(location, i32(dispatch_layer()), size)];
+    let water = cell.y + erosion.rain;
+    let moved = dot(hydraulic_outflow(location, size), vec4<f32>(1.0));
+
+    var height = cell.x;
+    var new_water = water - moved;
+    var sediment = cell.z * (1.0 - moved / max(water, 1e-6));
+
+    for (var i = 0u; i < 4u; i++) {
+        let neighbour = neighbour_index(location, i, size);
+        if neighbour < 0 { continue; }
+        let other = cells_in[neighbour];
+        let inflow = hydraulic_outflow(neighbour_location(location, i, size), size)[i ^ 1u];
+        new_water += inflow;
+        sediment += other.z * inflow / max(other.y + erosion.rain, 1e-6);
+    }
+
+    let capacity = erosion.capacity * moved;
+    if sediment > capacity {
+        let deposited = (sediment - capacity) * erosion.deposition;
+        height += deposited;
+        sediment -= deposited;
+    } else {
+        let eroded = min((capacity - sediment) * erosion.solubility, height);
+        height -= eroded;
+        sediment += eroded;
+    }
+
+    new_water *= 1.0 - erosion.evaporation;
+    return vec4<f32>(height, new_water, sediment, 0.0);
+}
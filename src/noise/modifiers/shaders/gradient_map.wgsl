@@ -0,0 +1,73 @@
+#import bevy_compute_noise::dispatch::{dispatch_location, outside_dispatch, dispatch_layer}
+
+const LINEAR: u32 = 0u;
+const CONSTANT: u32 = 1u;
+const SMOOTH_STEP: u32 = 2u;
+
+struct GradientMap {
+    stop_count: u32,
+    interpolation: u32,
+}
+
+struct GradientStop {
+    color: vec4<f32>,
+    position: f32,
+}
+
+#ifdef ARRAY
+    @group(0) @binding(0) var input_texture: texture_storage_2d_array<rgba8unorm, read_write>;
+    @group(0) @binding(1) var output_texture: texture_storage_2d_array<rgba8unorm, read_write>;
+#else ifdef 2D
+    @group(0) @binding(0) var input_texture: texture_storage_2d<rgba8unorm, read_write>;
+    @group(0) @binding(1) var output_texture: texture_storage_2d<rgba8unorm, read_write>;
+#endif
+#ifdef 3D
+    @group(0) @binding(0) var input_texture: texture_storage_3d<rgba8unorm, read_write>;
+    @group(0) @binding(1) var output_texture: texture_storage_3d<rgba8unorm, read_write>;
+#endif
+@group(0) @binding(2) var<uniform> gradient_map: GradientMap;
+@group(0) @binding(3) var<storage, read> stops: array<GradientStop>;
+
+#ifdef 2D
+    @compute @workgroup_size(16, 16)
+#endif
+#ifdef 3D
+    @compute @workgroup_size(8, 8, 4)
+#endif
+fn main(@builtin(global_invocation_id) invocation_id: vec3<u32>) {
+    let texel = dispatch_location(invocation_id);
+    if outside_dispatch(texel) { return; }
+    #ifdef 2D
+        let location = texel.xy;
+    #endif
+    #ifdef 3D
+        let location = texel;
+    #endif
+
+    #ifdef ARRAY
+        let value = textureLoad(input_texture, location, dispatch_layer()).r;
+        textureStore(output_texture, location, dispatch_layer(), sample_gradient(value));
+    #else
+        let value = textureLoad(input_texture, location).r;
+        textureStore(output_texture, location, sample_gradient(value));
+    #endif
+}
+
+fn sample_gradient(value: f32) -> vec4<f32> {
+    if value <= stops[0].position { return stops[0].color; }
+
+    for (var i = 1u; i < gradient_map.stop_count; i++) {
+        let end = stops[i];
+        if value >= end.position { continue; }
+
+        let start = stops[i - 1u];
+        let t = (value - start.position) / max(end.position - start.position, 1e-6);
+        switch gradient_map.interpolation {
+            case CONSTANT: { return start.color; }
+            case SMOOTH_STEP: { return mix(start.color, end.color, smoothstep(0.0, 1.0, t)); }
+            default: { return mix(start.color, end.color, t); }
+        }
+    }
+
+    return stops[gradient_map.stop_count - 1u].color;
+}
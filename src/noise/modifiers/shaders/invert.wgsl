@@ -1,24 +1,40 @@
-#ifdef 2D
+#import bevy_compute_noise::dispatch::{dispatch_location, outside_dispatch, dispatch_layer}
+
+#ifdef ARRAY
+    @group(0) @binding(0) var input_texture: texture_storage_2d_array<rgba8unorm, read_write>;
+    @group(0) @binding(1) var output_texture: texture_storage_2d_array<rgba8unorm, read_write>;
+    @compute @workgroup_size(16, 16)
+#else ifdef 2D
     @group(0) @binding(0) var input_texture: texture_storage_2d<rgba8unorm, read_write>;
     @group(0) @binding(1) var output_texture: texture_storage_2d<rgba8unorm, read_write>;
-    @compute @workgroup_size(32, 32)
+    @compute @workgroup_size(16, 16)
 #endif
 #ifdef 3D
     @group(0) @binding(0) var input_texture: texture_storage_3d<rgba8unorm, read_write>;
     @group(0) @binding(1) var output_texture: texture_storage_3d<rgba8unorm, read_write>;
-    @compute @workgroup_size(8, 8, 8)
+    @compute @workgroup_size(8, 8, 4)
 #endif
 fn main(@builtin(global_invocation_id) invocation_id: vec3<u32>) {
+    let texel = dispatch_location(invocation_id);
+    if outside_dispatch(texel) { return; }
     #ifdef 2D
-        let location = invocation_id.xy; 
+        let location = texel.xy;
     #endif
     #ifdef 3D
-        let location = invocation_id.xyz; 
+        let location = texel;
     #endif
 
-    var value = textureLoad(input_texture, location);
+    #ifdef ARRAY
+        var value = textureLoad(input_texture, location, dispatch_layer());
+    #else
+        var value = textureLoad(input_texture, location);
+    #endif
     value = invert(value);
-    textureStore(output_texture, location, value);
+    #ifdef ARRAY
+        textureStore(output_texture, location, dispatch_layer(), value);
+    #else
+        textureStore(output_texture, location, value);
+    #endif
 }
 
 fn invert(value: f32) -> f32 {
@@ -0,0 +1,119 @@
+#import bevy_compute_noise::dispatch::{dispatch_location, outside_dispatch, dispatch_layer, cell_index}
+
+const TILEABLE: u32 = 1u;
+
+const STAGE_LOAD: u32 = 0u;
+const STAGE_FILTER: u32 = 1u;
+const STAGE_STORE: u32 = 2u;
+
+const DILATE: u32 = 0u;
+
+const SQUARE: u32 = 0u;
+const DISC: u32 = 1u;
+const SPHERE: u32 = 2u;
+
+struct Morphology {
+    stage: u32,
+    operation: u32,
+    radius: u32,
+    element: u32,
+    flags: u32,
+}
+
+#ifdef ARRAY
+    @group(0) @binding(0) var input_texture: texture_storage_2d_array<rgba8unorm, read_write>;
+    @group(0) @binding(1) var output_texture: texture_storage_2d_array<rgba8unorm, read_write>;
+#else ifdef 2D
+    @group(0) @binding(0) var input_texture: texture_storage_2d<rgba8unorm, read_write>;
+    @group(0) @binding(1) var output_texture: texture_storage_2d<rgba8unorm, read_write>;
+#endif
+#ifdef 3D
+    @group(0) @binding(0) var input_texture: texture_storage_3d<rgba8unorm, read_write>;
+    @group(0) @binding(1) var output_texture: texture_storage_3d<rgba8unorm, read_write>;
+#endif
+@group(0) @binding(2) var<uniform> morphology: Morphology;
+@group(0) @binding(3) var<storage, read> values_in: array<f32>;
+@group(0) @binding(4) var<storage, read_write> values_out: array<f32>;
+
+#ifdef 2D
+    @compute @workgroup_size(16, 16)
+#endif
+#ifdef 3D
+    @compute @workgroup_size(8, 8, 4)
+#endif
+fn main(@builtin(global_invocation_id) invocation_id: vec3<u32>) {
+    #ifdef 2D
+        let size = vec3<i32>(vec2<i32>(textureDimensions(output_texture)), 1);
+    #endif
+    #ifdef 3D
+        let size = vec3<i32>(textureDimensions(output_texture));
+    #endif
+    let texel = dispatch_location(invocation_id);
+    if outside_dispatch(texel) { return; }
+    let location = vec3<i32>(texel);
+
+    let index = cell_index(location, size);
+    switch morphology.stage {
+        case STAGE_LOAD: {
+            #ifdef ARRAY
+                values_out[index] = textureLoad(input_texture, location.xy, dispatch_layer()).r;
+            #else ifdef 2D
+                values_out[index] = textureLoad(input_texture, location.xy).r;
+            #endif
+            #ifdef 3D
+                values_out[index] = textureLoad(input_texture, location).r;
+            #endif
+        }
+        case STAGE_FILTER: {
+            values_out[index] = morph(location, size);
+        }
+        case STAGE_STORE: {
+            let value = vec4<f32>(morph(location, size), 0.0, 0.0, 1.0);
+            #ifdef ARRAY
+                textureStore(output_texture, location.xy, dispatch_layer(), value);
+            #else ifdef 2D
+                textureStore(output_texture, location.xy, value);
+            #endif
+            #ifdef 3D
+                textureStore(output_texture, location, value);
+            #endif
+        }
+        default: {}
+    }
+}
+
+fn in_element(offset: vec3<i32>, radius: i32) -> bool {
+    let radius_squared = radius * radius;
+    switch morphology.element {
+        case DISC: { return dot(offset.xy, offset.xy) <= radius_squared; }
+        case SPHERE: { return dot(offset, offset) <= radius_squared; }
+        default: { return true; }
+    }
+}
+
+fn morph(location: vec3<i32>, size: vec3<i32>) -> f32 {
+    let dilate = morphology.operation == DILATE;
+    let radius = i32(morphology.radius);
+    let depth = select(radius, 0, size.z == 1 || morphology.element == DISC);
+
+    var result = select(1.0, 0.0, dilate);
+    for (var z = -depth; z <= depth; z++) {
+        for (var y = -radius; y <= radius; y++) {
+            for (var x = -radius; x <= radius; x++) {
+                let offset = vec3<i32>(x, y, z);
+                if !in_element(offset, radius) { continue; }
+
+                var neighbour = location + offset;
+                if (morphology.flags & TILEABLE) != 0u {
+                    neighbour = ((neighbour % size) + size) % size;
+                } else {
+                    neighbour = clamp(neighbour, vec3<i32>(0), size - 1);
+                }
+
+                let value = values_in[cell_index(neighbour, size)];
+                result = select(min(result, value), max(result, value), dilate);
+            }
+        }
+    }
+    return result;
+}
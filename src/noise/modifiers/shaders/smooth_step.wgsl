@@ -0,0 +1,48 @@
+#import bevy_compute_noise::dispatch::{dispatch_location, outside_dispatch, dispatch_layer}
+
+struct SmoothStep {
+    edge0: f32,
+    edge1: f32,
+}
+
+#ifdef ARRAY
+    @group(0) @binding(0) var input_texture: texture_storage_2d_array<rgba8unorm, read_write>;
+    @group(0) @binding(1) var output_texture: texture_storage_2d_array<rgba8unorm, read_write>;
+#else ifdef 2D
+    @group(0) @binding(0) var input_texture: texture_storage_2d<rgba8unorm, read_write>;
+    @group(0) @binding(1) var output_texture: texture_storage_2d<rgba8unorm, read_write>;
+#endif
+#ifdef 3D
+    @group(0) @binding(0) var input_texture: texture_storage_3d<rgba8unorm, read_write>;
+    @group(0) @binding(1) var output_texture: texture_storage_3d<rgba8unorm, read_write>;
+#endif
+@group(0) @binding(2) var<uniform> config: SmoothStep;
+
+#ifdef 2D
+    @compute @workgroup_size(16, 16)
+#endif
+#ifdef 3D
+    @compute @workgroup_size(8, 8, 4)
+#endif
+fn main(@builtin(global_invocation_id) invocation_id: vec3<u32>) {
+    let texel = dispatch_location(invocation_id);
+    if outside_dispatch(texel) { return; }
+    #ifdef 2D
+        let location = texel.xy;
+    #endif
+    #ifdef 3D
+        let location = texel;
+    #endif
+
+    #ifdef ARRAY
+        let value = smooth_step(textureLoad(input_texture, location, dispatch_layer()).r, config);
+        textureStore(output_texture, location, dispatch_layer(), vec4<f32>(value, 0.0, 0.0, 1.0));
+    #else
+        let value = smooth_step(textureLoad(input_texture, location).r, config);
+        textureStore(output_texture, location, vec4<f32>(value, 0.0, 0.0, 1.0));
+    #endif
+}
+
+fn smooth_step(value: f32, smooth_step: SmoothStep) -> f32 {
+    return smoothstep(smooth_step.edge0, smooth_step.edge1, value);
+}
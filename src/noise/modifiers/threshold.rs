@@ -0,0 +1,131 @@
+use bevy::{app::App, asset::embedded_asset, prelude::*, render::render_resource::{binding_types::uniform_buffer_sized, BindGroupLayoutEntryBuilder, Buffer, ShaderRef}};
+use bytemuck::{Pod, Zeroable};
+use serde::{Deserialize, Serialize};
+
+use crate::{buffer_pool::ComputeNoiseBufferPool, image::ComputeNoiseSize, noise::ComputeNoise, render::pipeline::NoiseOp, shader::ComputeNoiseShader};
+
+use super::ComputeNoiseModifier;
+
+/// Outputs 1.0 where the value is at or above `value` and 0.0 everywhere else.
+#[derive(Clone, Copy, Reflect, PartialEq, Debug, Pod, Zeroable, Serialize, Deserialize)]
+#[reflect(Default, Serialize, Deserialize)]
+#[repr(C)]
+pub struct Threshold {
+    pub value: f32,
+    pub flags: ThresholdFlags,
+}
+
+#[derive(Clone, Copy, Reflect, PartialEq, Eq, Hash, Debug, Default, Pod, Zeroable, Serialize, Deserialize)]
+#[reflect(Default, Debug, PartialEq, Hash, Serialize, Deserialize)]
+#[serde(transparent)]
+#[repr(transparent)]
+pub struct ThresholdFlags(u32);
+
+bitflags::bitflags! {
+    impl ThresholdFlags: u32 {
+        const INVERT = 1 << 0;
+    }
+}
+
+impl Default for Threshold {
+    fn default() -> Self {
+        Self {
+            value: 0.5,
+            flags: ThresholdFlags::empty(),
+        }
+    }
+}
+
+impl ComputeNoise for Threshold {
+    const NOISE_OP: NoiseOp = NoiseOp::Modifier;
+
+    fn buffers(&self, pool: &mut ComputeNoiseBufferPool, _size: ComputeNoiseSize) -> Vec<Buffer> {
+        vec![
+            pool.uniform("threshold_buffer", bytemuck::cast_slice(&[*self]))
+        ]
+    }
+}
+
+impl ComputeNoiseModifier for Threshold {
+    fn embed_shaders(app: &mut App) {
+        embedded_asset!(app, "shaders/threshold.wgsl");
+    }
+
+    fn shader() -> ShaderRef {
+        "embedded://bevy_compute_noise/noise/modifiers/shaders/threshold.wgsl".into()
+    }
+
+    fn buffer_layout() -> Vec<BindGroupLayoutEntryBuilder> {
+        vec![uniform_buffer_sized(false, None)]
+    }
+}
+
+impl ComputeNoiseShader for Threshold {
+    fn function_name() -> &'static str {
+        "threshold"
+    }
+
+    fn import_path() -> &'static str {
+        "bevy_compute_noise::threshold"
+    }
+
+    fn struct_name() -> Option<&'static str> {
+        Some("Threshold")
+    }
+}
+
+/// Smooth Hermite step between `edge0` and `edge1`, matching WGSL's `smoothstep`.
+#[derive(Clone, Copy, Reflect, PartialEq, Debug, Pod, Zeroable, Serialize, Deserialize)]
+#[reflect(Default, Serialize, Deserialize)]
+#[repr(C)]
+pub struct SmoothStep {
+    pub edge0: f32,
+    pub edge1: f32,
+}
+
+impl Default for SmoothStep {
+    fn default() -> Self {
+        Self {
+            edge0: 0.4,
+            edge1: 0.6,
+        }
+    }
+}
+
+impl ComputeNoise for SmoothStep {
+    const NOISE_OP: NoiseOp = NoiseOp::Modifier;
+
+    fn buffers(&self, pool: &mut ComputeNoiseBufferPool, _size: ComputeNoiseSize) -> Vec<Buffer> {
+        vec![
+            pool.uniform("smooth_step_buffer", bytemuck::cast_slice(&[*self]))
+        ]
+    }
+}
+
+impl ComputeNoiseModifier for SmoothStep {
+    fn embed_shaders(app: &mut App) {
+        embedded_asset!(app, "shaders/smooth_step.wgsl");
+    }
+
+    fn shader() -> ShaderRef {
+        "embedded://bevy_compute_noise/noise/modifiers/shaders/smooth_step.wgsl".into()
+    }
+
+    fn buffer_layout() -> Vec<BindGroupLayoutEntryBuilder> {
+        vec![uniform_buffer_sized(false, None)]
+    }
+}
+
+impl ComputeNoiseShader for SmoothStep {
+    fn function_name() -> &'static str {
+        "smooth_step"
+    }
+
+    fn import_path() -> &'static str {
+        "bevy_compute_noise::smooth_step"
+    }
+
+    fn struct_name() -> Option<&'static str> {
+        Some("SmoothStep")
+    }
+}
@@ -0,0 +1,33 @@
+#define_import_path bevy_compute_noise::dispatch
+
+// Region of the texture covered by a single dispatch, large 3D textures are dispatched in several z slabs.
+// Layered textures are dispatched one layer at a time.
+struct ComputeNoiseDispatch {
+    offset: vec3<u32>,
+    layer: u32,
+    end: vec3<u32>,
+}
+@group(1) @binding(0) var<uniform> dispatch: ComputeNoiseDispatch;
+
+fn dispatch_location(invocation_id: vec3<u32>) -> vec3<u32> {
+    return invocation_id + dispatch.offset;
+}
+
+fn outside_dispatch(location: vec3<u32>) -> bool {
+    return any(location >= dispatch.end);
+}
+
+fn dispatch_layer() -> u32 {
+    return dispatch.layer;
+}
+
+// Index of a texel in the storage buffers multi-pass noise keeps its state in. Layers of an array texture are
+// stored one after another, the z slices of a 3D texture are indexed like layers.
+fn layer_cell_index(location: vec2<i32>, layer: i32, size: vec2<i32>) -> i32 {
+    return (layer * size.y + location.y) * size.x + location.x;
+}
+
+// Index of a texel in the layer being dispatched.
+fn cell_index(location: vec3<i32>, size: vec3<i32>) -> i32 {
+    return layer_cell_index(location.xy, i32(dispatch.layer) + location.z, size.xy);
+}
@@ -0,0 +1,77 @@
+#define_import_path bevy_compute_noise::fbm
+
+#import bevy_compute_noise::perlin::{Perlin, perlin_2d, perlin_3d}
+#import bevy_compute_noise::worley::{Worley, worley_2d, worley_3d}
+
+const INVERT: u32 = 1u;
+const BILLOWY: u32 = 2u;
+
+// Matches `Fbm` without the wrapped noise. The octaves are scaled so they sum to the range of one.
+struct Fbm {
+    octaves: u32,
+    lacunarity: f32,
+    persistence: f32,
+    flags: u32,
+}
+
+fn first_amplitude(fbm: Fbm) -> f32 {
+    // The amplitudes are a geometric series, which is just the number of octaves when they don't fall off.
+    if fbm.persistence == 1.0 { return 1.0 / f32(fbm.octaves); }
+    let max_amplitude = (1.0 - pow(fbm.persistence, f32(fbm.octaves))) / (1.0 - fbm.persistence);
+    return 1.0 / max_amplitude;
+}
+
+fn octave(value: f32, fbm: Fbm) -> f32 {
+    var octave_value = value;
+    if (fbm.flags & INVERT) != 0u { octave_value = 1.0 - octave_value; }
+    if (fbm.flags & BILLOWY) != 0u { octave_value = abs(octave_value); }
+    return octave_value;
+}
+
+fn fbm_perlin_2d(uv: vec2<f32>, perlin: Perlin, fbm: Fbm) -> f32 {
+    var value = 0.0;
+    var amplitude = first_amplitude(fbm);
+    var noise = perlin;
+    for (var i = 0u; i < fbm.octaves; i++) {
+        value += octave(perlin_2d(uv, noise), fbm) * amplitude;
+        noise.frequency *= fbm.lacunarity;
+        amplitude *= fbm.persistence;
+    }
+    return value;
+}
+
+fn fbm_perlin_3d(uv: vec3<f32>, perlin: Perlin, fbm: Fbm) -> f32 {
+    var value = 0.0;
+    var amplitude = first_amplitude(fbm);
+    var noise = perlin;
+    for (var i = 0u; i < fbm.octaves; i++) {
+        value += octave(perlin_3d(uv, noise), fbm) * amplitude;
+        noise.frequency *= fbm.lacunarity;
+        amplitude *= fbm.persistence;
+    }
+    return value;
+}
+
+fn fbm_worley_2d(uv: vec2<f32>, worley: Worley, fbm: Fbm) -> f32 {
+    var value = 0.0;
+    var amplitude = first_amplitude(fbm);
+    var noise = worley;
+    for (var i = 0u; i < fbm.octaves; i++) {
+        value += octave(worley_2d(uv, noise), fbm) * amplitude;
+        noise.frequency *= fbm.lacunarity;
+        amplitude *= fbm.persistence;
+    }
+    return value;
+}
+
+fn fbm_worley_3d(uv: vec3<f32>, worley: Worley, fbm: Fbm) -> f32 {
+    var value = 0.0;
+    var amplitude = first_amplitude(fbm);
+    var noise = worley;
+    for (var i = 0u; i < fbm.octaves; i++) {
+        value += octave(worley_3d(uv, noise), fbm) * amplitude;
+        noise.frequency *= fbm.lacunarity;
+        amplitude *= fbm.persistence;
+    }
+    return value;
+}
@@ -0,0 +1,106 @@
+#define_import_path bevy_compute_noise::math
+
+// Hashing and interpolation the noise functions are built from. There are no bindings in here,
+// so it can be imported by any shader, not only compute shaders writing noise.
+
+#import bevy_render::maths::PI
+
+const INFINITY = 3.402823e+38;
+
+const UI0 = 1597334673u;
+const UI1 = 3812015801u;
+const UI2 = vec2<u32>(UI0, UI1);
+const UI3 = vec3<u32>(UI0, UI1, 2798796415u);
+const UIF = (1.0 / f32(0xffffffffu));
+
+fn hash22(p: vec2<f32>) -> vec2<f32> {
+    var q = vec2<u32>(vec2<i32>(p));
+    q = (q.x ^ q.y) * UI2;
+    return -1.0 + 2.0 * vec2<f32>(q) * UIF;
+}
+
+fn hash33(p: vec3<f32>) -> vec3<f32> {
+    var q = vec3<u32>(vec3<i32>(p)) * UI3;
+    q = (q.x ^ q.y ^ q.z) * UI3 ;
+    return -1.0 + 2.0 * vec3<f32>(q) * UIF;
+}
+
+fn remap(x: f32, a: f32, b: f32, c: f32, d: f32) -> f32 {
+    return (((x - a) / (b - a)) * (d - c)) + c;
+}
+
+fn interpolate_cubic(w: vec2<f32>) -> vec2<f32> {
+   return (3.0 - w * 2.0) * w * w;
+}
+
+fn interpolate_quintic(w: vec2<f32>) -> vec2<f32> {
+   let w3 = w * w * w;
+   let w4 = w3 * w;
+   let w5 = w4 * w;
+   return 6.0 * w5 - 15.0 * w4 + 10.0 * w3;
+}
+
+fn interpolate_cubic_3d(w: vec3<f32>) -> vec3<f32> {
+   return (3.0 - w * 2.0) * w * w;
+}
+
+fn interpolate_quintic_3d(w: vec3<f32>) -> vec3<f32> {
+   let w3 = w * w * w;
+   let w4 = w3 * w;
+   let w5 = w4 * w;
+   return 6.0 * w5 - 15.0 * w4 + 10.0 * w3;
+}
+
+// PCG random numbers, the same as `bevy_pbr::utils`, so importing this module doesn't need `bevy_pbr`.
+fn rand_u(state: ptr<function, u32>) -> u32 {
+    *state = *state * 747796405u + 2891336453u;
+    let word = ((*state >> ((*state >> 28u) + 4u)) ^ *state) * 277803737u;
+    return (word >> 22u) ^ word;
+}
+
+// In the range [0, 1].
+fn rand_f(state: ptr<function, u32>) -> f32 {
+    return f32(rand_u(state)) * bitcast<f32>(0x2f800004u);
+}
+
+fn rand_vec2f(state: ptr<function, u32>) -> vec2<f32> {
+    return vec2(rand_f(state), rand_f(state));
+}
+
+fn rand_vec3f(state: ptr<function, u32>) -> vec3<f32> {
+    return vec3(rand_f(state), rand_f(state), rand_f(state));
+}
+
+fn random_gradient_2d(seed: u32, pos: vec2<u32>) -> vec2<f32> {
+    var state = seed + pos.x * 1597u + pos.y * 51749u;
+    let v = rand_vec2f(&state) * 2.0 - 1.0;
+    return normalize(v);
+}
+
+fn random_gradient_3d(seed: u32, pos: vec3<u32>) -> vec3<f32> {
+    var state = seed + pos.x * 1597u + pos.y * 51749u + pos.z * 241u;
+    let v = rand_vec3f(&state) * 2.0 - 1.0;
+    return normalize(v);
+}
+
+// Direction through `uv` on a cube map face, faces are ordered +X, -X, +Y, -Y, +Z, -Z.
+fn cube_direction(face: u32, uv: vec2<f32>) -> vec3<f32> {
+    let p = uv * 2.0 - 1.0;
+    var direction: vec3<f32>;
+    switch face {
+        case 0u: { direction = vec3<f32>(1.0, -p.y, -p.x); }
+        case 1u: { direction = vec3<f32>(-1.0, -p.y, p.x); }
+        case 2u: { direction = vec3<f32>(p.x, 1.0, p.y); }
+        case 3u: { direction = vec3<f32>(p.x, -1.0, -p.y); }
+        case 4u: { direction = vec3<f32>(p.x, -p.y, 1.0); }
+        default: { direction = vec3<f32>(-p.x, -p.y, -1.0); }
+    }
+    return normalize(direction);
+}
+
+// Direction through `uv` on an equirectangular projection, with the poles at the top and bottom rows.
+fn equirectangular_direction(uv: vec2<f32>) -> vec3<f32> {
+    let longitude = (uv.x * 2.0 - 1.0) * PI;
+    let latitude = (0.5 - uv.y) * PI;
+    return vec3<f32>(cos(latitude) * sin(longitude), sin(latitude), cos(latitude) * cos(longitude));
+}
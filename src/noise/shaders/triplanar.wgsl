@@ -0,0 +1,35 @@
+#define_import_path bevy_compute_noise::triplanar
+
+// Sampling 3D noise textures in world space, for surfaces without uvs. Volumes should tile and sample
+// with repeat addressing, like images from `ComputeNoiseImage::create_mipmapped_image(size, true)`.
+// These use `textureSample`, so they can only be called from fragment shaders.
+
+// Blend weights of the three projections for a surface facing `normal`. Higher `sharpness` narrows the blend.
+fn triplanar_weights(normal: vec3<f32>, sharpness: f32) -> vec3<f32> {
+    let weights = pow(abs(normal), vec3(sharpness));
+    return weights / (weights.x + weights.y + weights.z);
+}
+
+// Samples `volume` at `position * scale`, it repeats every `1.0 / scale` world units.
+fn sample_volume(volume: texture_3d<f32>, volume_sampler: sampler, position: vec3<f32>, scale: f32) -> vec4<f32> {
+    return textureSample(volume, volume_sampler, position * scale);
+}
+
+// Samples `volume` once looking down each axis and blends the samples by `triplanar_weights`.
+// Each projection puts the axis it looks down in the volume's depth, so features in the volume's
+// slices face the surface, and the position along the axis keeps it continuous in 3D.
+fn sample_triplanar(
+    volume: texture_3d<f32>,
+    volume_sampler: sampler,
+    position: vec3<f32>,
+    normal: vec3<f32>,
+    scale: f32,
+    sharpness: f32,
+) -> vec4<f32> {
+    let p = position * scale;
+    let weights = triplanar_weights(normal, sharpness);
+    let x = textureSample(volume, volume_sampler, p.zyx);
+    let y = textureSample(volume, volume_sampler, p.xzy);
+    let z = textureSample(volume, volume_sampler, p.xyz);
+    return x * weights.x + y * weights.y + z * weights.z;
+}
@@ -1,18 +1,36 @@
+use std::{any::TypeId, ops::Range, sync::Arc};
+
 use bevy::{
     prelude::*, render::{
-        render_resource::{BindGroup, Buffer, CachedComputePipelineId, TextureDimension}, renderer::RenderDevice,
-    }
+        render_resource::{BindGroup, Buffer, CachedComputePipelineId, TextureViewDimension},
+    }, utils::HashSet,
 };
 
-use crate::{image::ComputeNoiseSize, noise::{ComputeNoise, ErasedComputeNoise}, render::pipeline::{ComputeNoisePipelineKey, NoiseOp}};
+use crate::{buffer_pool::ComputeNoiseBufferPool, error::ComputeNoiseError, image::{ComputeNoiseImage, ComputeNoiseRegion, ComputeNoiseSize}, noise::{modifiers::{Downsample, DownsampleFlags}, ComputeNoise, ErasedComputeNoise}, render::pipeline::{pipeline_dimension, ComputeNoisePipelineKey, NoiseOp}};
 
 pub(crate) struct ComputeNoiseInstruction {
     pub images: Vec<Handle<Image>>,
     pub noise: ErasedComputeNoise,
     pub op: NoiseOp,
+    /// Stop dispatching the sequence after this instruction until the next frame.
+    pub frame_break: bool,
+    pub priority: i32,
+    pub region: Option<ComputeNoiseRegion>,
 }
 pub struct ComputeNoiseSequence(pub(crate) Vec<ComputeNoiseInstruction>);
 
+#[cfg(test)]
+impl ComputeNoiseSequence {
+    /// Maps every instruction of a multi-pass sequence, which are all the same pass type.
+    pub(crate) fn passes<T: ComputeNoise, R>(&self, map: impl Fn(&T) -> R) -> Vec<R> {
+        self.0.iter().map(|instruction| map(instruction.noise.as_noise::<T>().unwrap())).collect()
+    }
+
+    pub(crate) fn frame_breaks(&self) -> Vec<bool> {
+        self.0.iter().map(|instruction| instruction.frame_break).collect()
+    }
+}
+
 pub enum QueueNoiseOp {
     Generate(ErasedComputeNoise),
     Modify(Handle<Image>, ErasedComputeNoise),
@@ -55,16 +73,25 @@ impl IntoNoiseSequence for QueueNoiseOp {
                 images: vec![output],
                 noise,
                 op: NoiseOp::Generator,
+                frame_break: false,
+                priority: 0,
+                region: None,
             },
             QueueNoiseOp::Modify(input, noise) => ComputeNoiseInstruction {
                 images: vec![input, output],
                 noise,
                 op: NoiseOp::Modifier,
+                frame_break: false,
+                priority: 0,
+                region: None,
             },
             QueueNoiseOp::Combine(input1, input2, noise) => ComputeNoiseInstruction {
                 images: vec![input1, input2, output],
                 noise,
                 op: NoiseOp::Combiner,
+                frame_break: false,
+                priority: 0,
+                region: None,
             },
         };
         ComputeNoiseSequence(vec![instruction])
@@ -105,62 +132,236 @@ impl_into_noise_sequence_tuple! {0: A, 1: B, 2: C, 3: D, 4: E, 5: F, 6: G, 7: H,
 impl_into_noise_sequence_tuple! {0: A, 1: B, 2: C, 3: D, 4: E, 5: F, 6: G, 7: H, 8: I, 9: J, 10: K}
 impl_into_noise_sequence_tuple! {0: A, 1: B, 2: C, 3: D, 4: E, 5: F, 6: G, 7: H, 8: I, 9: J, 10: K, 11: L}
 
+/// Runs each element in order, for stacks only known at runtime.
+impl<T: IntoNoiseSequence> IntoNoiseSequence for Vec<T> {
+    fn into_sequence(self, image: Handle<Image>) -> ComputeNoiseSequence {
+        ComputeNoiseSequence(self.into_iter().flat_map(|noise| noise.into_sequence(image.clone()).0).collect())
+    }
+}
+
 // Main World
 #[derive(Resource, Default)]
 pub struct ComputeNoiseQueue {
     pub(crate) queue: Vec<ComputeNoiseSequence>,
+    pub(crate) warm_up: Vec<(ComputeNoisePipelineKey, &'static str)>,
+    /// Whether the render world had compiled every pipeline it was asked to warm up at the end of the last frame.
+    pub(crate) warm_up_compiled: bool,
+    pub(crate) warm_up_failed: Vec<(&'static str, TextureViewDimension)>,
+    registered: HashSet<TypeId>,
 }
 impl ComputeNoiseQueue {
-    pub fn queue<T: IntoNoiseSequence>(&mut self, output: Handle<Image>, operations: T) {
-        self.queue.push(operations.into_sequence(output));
+    /// Queues a sequence of noise operations to be written to `output`.
+    ///
+    /// Returns an error without queuing anything if a noise type in the sequence hasn't been registered.
+    /// Sequences whose images haven't loaded yet are kept in the queue until they have. Once they've loaded,
+    /// sequences that don't fit their images, like 2D only noise on a 3D texture, are dropped with an error logged.
+    pub fn queue<T: IntoNoiseSequence>(&mut self, output: Handle<Image>, operations: T) -> Result<(), ComputeNoiseError> {
+        self.queue_with_priority(output, operations, 0)
+    }
+
+    /// Same as [`queue`](Self::queue), but sequences with a higher priority are dispatched first
+    /// when [`ComputeNoiseSettings`](crate::settings::ComputeNoiseSettings) limits how much runs each frame.
+    pub fn queue_with_priority<T: IntoNoiseSequence>(&mut self, output: Handle<Image>, operations: T, priority: i32) -> Result<(), ComputeNoiseError> {
+        self.push(output, operations, priority, None)
+    }
+
+    /// Same as [`queue`](Self::queue), but only the texels in `region` are written.
+    ///
+    /// Returns [`ComputeNoiseError::UnsupportedRegion`] for multi-pass noise like [`ThermalErosion`](crate::noise::modifiers::ThermalErosion),
+    /// which needs the whole texture.
+    pub fn queue_region<T: IntoNoiseSequence>(&mut self, output: Handle<Image>, region: ComputeNoiseRegion, operations: T) -> Result<(), ComputeNoiseError> {
+        self.push(output, operations, 0, Some(region))
+    }
+
+    fn push<T: IntoNoiseSequence>(&mut self, output: Handle<Image>, operations: T, priority: i32, region: Option<ComputeNoiseRegion>) -> Result<(), ComputeNoiseError> {
+        let mut sequence = operations.into_sequence(output);
+        self.check_registered(&sequence)?;
+        if region.is_some() {
+            if let Some(instruction) = sequence.0.iter().find(|instruction| !instruction.noise.supports_region) {
+                return Err(ComputeNoiseError::UnsupportedRegion(instruction.noise.type_name));
+            }
+        }
+
+        for instruction in sequence.0.iter_mut() {
+            instruction.priority = priority;
+            instruction.region = region;
+        }
+        self.queue.push(sequence);
+        Ok(())
+    }
+
+    /// Starts compiling the pipelines `operations` need for textures of `dimension`, so the first
+    /// noise queued with them isn't delayed. The values in `operations` don't matter, only their types,
+    /// and noise is always written as `Rgba8Unorm` so there's no format to pick.
+    ///
+    /// [`ComputeNoisePipelinesReady`] turns true once every pipeline requested this way has compiled.
+    pub fn warm_up<T: IntoNoiseSequence>(&mut self, operations: T, dimension: TextureViewDimension) -> Result<(), ComputeNoiseError> {
+        let sequence = operations.into_sequence(Handle::default());
+        self.check_registered(&sequence)?;
+        if dimension == TextureViewDimension::D3 {
+            if let Some(instruction) = sequence.0.iter().find(|instruction| !instruction.noise.supports_3d) {
+                return Err(ComputeNoiseError::Unsupported3d(instruction.noise.type_name));
+            }
+        }
+
+        self.warm_up.extend(sequence.0.iter().map(|instruction| (
+            ComputeNoisePipelineKey {
+                type_id: instruction.noise.type_id,
+                dimension: pipeline_dimension(dimension, instruction.op),
+                op: instruction.op,
+                variant: instruction.noise.variant,
+            },
+            instruction.noise.type_name,
+        )));
+        Ok(())
+    }
+
+    /// Same as [`ComputeNoisePipelinesReady`], but false as soon as more pipelines are requested,
+    /// instead of from the end of the frame.
+    pub fn pipelines_ready(&self) -> bool {
+        self.warm_up.is_empty() && self.warm_up_compiled && self.warm_up_failed.is_empty()
+    }
+
+    /// Noise types and dimensions whose warmed up pipelines failed to compile or have no shader, they'll never be ready.
+    pub fn failed_pipelines(&self) -> &[(&'static str, TextureViewDimension)] {
+        &self.warm_up_failed
+    }
+
+    fn check_registered(&self, sequence: &ComputeNoiseSequence) -> Result<(), ComputeNoiseError> {
+        match sequence.0.iter().find(|instruction| !self.registered.contains(&instruction.noise.type_id)) {
+            Some(instruction) => Err(ComputeNoiseError::UnregisteredType(instruction.noise.type_name)),
+            None => Ok(()),
+        }
+    }
+
+    pub(crate) fn register<T: ComputeNoise>(&mut self) {
+        self.registered.insert(TypeId::of::<T>());
+    }
+
+    /// Whether any noise in the queue writes to `image`.
+    pub(crate) fn is_writing(&self, image: &Handle<Image>) -> bool {
+        self.queue.iter()
+            .flat_map(|sequence| sequence.0.iter())
+            .any(|instruction| instruction.images.last() == Some(image))
     }
 }
 
+/// Whether every pipeline requested with [`ComputeNoiseQueue::warm_up`] has finished compiling, for keeping
+/// a loading screen up until noise can be generated without waiting. Updated at the end of each frame, and
+/// never true while a warmed up pipeline has failed, see [`ComputeNoiseQueue::failed_pipelines`].
+#[derive(Resource, Default, Clone, Copy, PartialEq, Eq, Debug)]
+pub struct ComputeNoisePipelinesReady(pub bool);
+
 pub fn prepare_compute_noise_buffers(
     images: Res<Assets<Image>>,
-    render_device: Res<RenderDevice>,
+    mut buffer_pool: ResMut<ComputeNoiseBufferPool>,
     mut noise_queue: ResMut<ComputeNoiseQueue>,
     mut noise_buffer_queue: ResMut<ComputeNoiseBufferQueue>,
 ) {
-    for item in &noise_queue.queue {
-        let sizes: Vec<ComputeNoiseSize> = item.0.iter()
+    buffer_pool.tick();
+
+    noise_queue.queue.retain(|item| {
+        let Some(sizes) = item.0.iter()
             .flat_map(|instruction| instruction.images.iter())
             .map(|image_handle| {
-                images.get(image_handle).unwrap().texture_descriptor.size.into()
+                images.get(image_handle).map(ComputeNoiseSize::from)
             })
-            .collect();
+            .collect::<Option<Vec<_>>>()
+        else {
+            return true;
+        };
 
-        if !sizes.windows(2).all(|window| TextureDimension::from(window[0]) == TextureDimension::from(window[1])) {
-            error!("Not all images have the same dimension - did not queue compute noise.");
-            continue;
+        let dimension = |size: ComputeNoiseSize| (TextureViewDimension::from(size), size.layers());
+        if !sizes.windows(2).all(|window| dimension(window[0]) == dimension(window[1])) {
+            error!("{} - did not queue compute noise.", ComputeNoiseError::DimensionMismatch);
+            return false;
         }
+        if sizes.first().is_some_and(|&size| TextureViewDimension::from(size) == TextureViewDimension::D3) {
+            if let Some(instruction) = item.0.iter().find(|instruction| !instruction.noise.supports_3d) {
+                error!("{} - did not queue compute noise.", ComputeNoiseError::Unsupported3d(instruction.noise.type_name));
+                return false;
+            }
+        }
+
+        let output_sizes = item.0.iter().map(|instruction| -> ComputeNoiseSize {
+            images.get(instruction.images.last().unwrap()).unwrap().into()
+        });
+
+        let mut sequence_buffers: Vec<ComputeNoiseBuffers> = item.0.iter().zip(output_sizes)
+            .filter(|(instruction, size)| instruction.noise.runs_on(*size))
+            .map(|(instruction, size)| {
+                ComputeNoiseBuffers {
+                    key: ComputeNoisePipelineKey {
+                        type_id: instruction.noise.type_id,
+                        dimension: pipeline_dimension(size.into(), instruction.op),
+                        op: instruction.op,
+                        variant: instruction.noise.variant,
+                    },
+                    type_name: instruction.noise.type_name,
+                    images: instruction.images.clone(),
+                    buffers: instruction.noise.buffers(&mut buffer_pool, size),
+                    leases: buffer_pool.take_leases(),
+                    size,
+                    frame_break: instruction.frame_break,
+                    priority: instruction.priority,
+                    region: instruction.region,
+                    mip_level: 0,
+                }
+            }).collect();
+
+        // Mip chains are downsampled once everything in the sequence has been written.
+        let mut written = HashSet::new();
+        let outputs = item.0.iter()
+            .map(|instruction| instruction.images.last().unwrap())
+            .filter(|output| written.insert(output.id()));
+        for output in outputs {
+            let image = images.get(output).unwrap();
+            let size = ComputeNoiseSize::from(image);
+            let flags = if ComputeNoiseImage::is_tileable(image) { DownsampleFlags::WRAP } else { DownsampleFlags::empty() };
+            let downsample = Downsample { flags };
 
-        let sequence_buffers: Vec<ComputeNoiseBuffers> = item.0.iter().zip(&sizes).map(|(instruction, size)| {
-            ComputeNoiseBuffers {
-                key: ComputeNoisePipelineKey {
-                    type_id: instruction.noise.type_id,
-                    dimension: (*size).into(),
-                    op: instruction.op,
-                },
-                images: instruction.images.clone(),
-                buffers: instruction.noise.buffers(&render_device),
-                size: *size,
+            for mip_level in 1..image.texture_descriptor.mip_level_count {
+                let mip_size = size.mip_size(mip_level);
+                sequence_buffers.push(ComputeNoiseBuffers {
+                    key: ComputeNoisePipelineKey {
+                        type_id: TypeId::of::<Downsample>(),
+                        dimension: pipeline_dimension(mip_size.into(), NoiseOp::Modifier),
+                        op: NoiseOp::Modifier,
+                        variant: 0,
+                    },
+                    type_name: Downsample::type_path(),
+                    images: vec![output.clone(), output.clone()],
+                    buffers: downsample.buffers(&mut buffer_pool, mip_size),
+                    leases: buffer_pool.take_leases(),
+                    size: mip_size,
+                    frame_break: false,
+                    priority: item.0[0].priority,
+                    region: item.0[0].region.map(|region| region.mip(mip_level)),
+                    mip_level,
+                });
             }
-        }).collect();
+        }
 
         noise_buffer_queue.queue.push(sequence_buffers);
-    }
-    
-    noise_queue.queue.clear();
+        false
+    });
 }
 
 // Main/Render World
 #[derive(Clone)]
 pub struct ComputeNoiseBuffers {
     pub key: ComputeNoisePipelineKey,
+    pub type_name: &'static str,
     pub images: Vec<Handle<Image>>,
     pub buffers: Vec<Buffer>,
+    /// Keeps pooled buffers from being reused until this has been dispatched.
+    pub(crate) leases: Vec<Arc<()>>,
     pub size: ComputeNoiseSize,
+    pub frame_break: bool,
+    pub priority: i32,
+    pub region: Option<ComputeNoiseRegion>,
+    /// Mip written to, when downsampling the input is read from the mip before it.
+    pub mip_level: u32,
 }
 
 #[derive(Resource, Clone, Default)]
@@ -175,9 +376,154 @@ pub struct RenderComputeNoise {
     pub bind_group: BindGroup,
     pub pipeline_id: CachedComputePipelineId,
     pub size: ComputeNoiseSize,
+    pub frame_break: bool,
+    pub priority: i32,
+    pub region: Option<ComputeNoiseRegion>,
+    /// Image written to, the last image the instruction binds.
+    pub output: AssetId<Image>,
+    /// First z slice, or layer of a layered texture, that hasn't been dispatched yet.
+    pub(crate) next_z: u32,
+    pub(crate) leases: Vec<Arc<()>>,
+}
+
+impl RenderComputeNoise {
+    /// Z slices of the region, or every layer of a layered texture, that need dispatching.
+    pub(crate) fn slices(&self) -> Range<u32> {
+        if self.size.is_layered() { return 0..self.size.layers(); }
+        let (start, end) = ComputeNoiseRegion::bounds(self.region, self.size);
+        start.z..end.z
+    }
 }
 
 #[derive(Default, Resource)]
 pub(crate) struct ComputeNoiseRenderQueue {
     pub queue: Vec<Vec<RenderComputeNoise>>,
+    /// Sequences waiting on their images to be uploaded to the GPU.
+    pub pending: Vec<Vec<ComputeNoiseBuffers>>,
+}
+
+impl ComputeNoiseRenderQueue {
+    /// Whether any noise that hasn't been dispatched yet writes to `image`.
+    pub fn is_writing(&self, image: AssetId<Image>) -> bool {
+        self.queue.iter().flatten().any(|render_noise| render_noise.output == image)
+            || self.pending.iter().flatten().any(|noise| noise.images.last().is_some_and(|output| output.id() == image))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn queue_rejects_unregistered_types() {
+        use bevy::reflect::TypePath;
+        use crate::noise::generators::Perlin;
+
+        let mut noise_queue = ComputeNoiseQueue::default();
+        assert_eq!(
+            noise_queue.queue(Handle::default(), Perlin::default()),
+            Err(ComputeNoiseError::UnregisteredType(Perlin::type_path())),
+        );
+
+        noise_queue.register::<Perlin>();
+        assert_eq!(noise_queue.queue(Handle::default(), Perlin::default()), Ok(()));
+        assert!(Perlin::default().input_image(Handle::default()).is_err());
+    }
+
+    #[test]
+    fn vec_sequence_keeps_op_order() {
+        use crate::noise::{generators::{Fbm, Perlin}, modifiers::{Invert, Threshold}};
+
+        let ops: Vec<QueueNoiseOp> = vec![Perlin::default().into(), Fbm::<Perlin>::default().into(), Invert.into(), Threshold::default().into()];
+        let sequence = ops.into_sequence(Handle::default());
+        assert_eq!(
+            sequence.0.iter().map(|instruction| (instruction.noise.type_id, instruction.op)).collect::<Vec<_>>(),
+            vec![
+                (TypeId::of::<Perlin>(), NoiseOp::Generator),
+                (TypeId::of::<Fbm<Perlin>>(), NoiseOp::Generator),
+                (TypeId::of::<Invert>(), NoiseOp::Modifier),
+                (TypeId::of::<Threshold>(), NoiseOp::Modifier),
+            ],
+        );
+    }
+
+    #[test]
+    fn warm_up_queues_pipeline_keys() {
+        use crate::noise::{generators::Perlin, modifiers::Invert};
+
+        let mut noise_queue = ComputeNoiseQueue::default();
+        assert!(noise_queue.warm_up(Perlin::default(), TextureViewDimension::D3).is_err());
+
+        noise_queue.register::<Perlin>();
+        noise_queue.register::<Invert>();
+        assert_eq!(noise_queue.warm_up((Perlin::default(), Invert), TextureViewDimension::D3), Ok(()));
+        assert_eq!(
+            noise_queue.warm_up.iter().map(|(key, _)| (key.type_id, key.dimension, key.op)).collect::<Vec<_>>(),
+            vec![
+                (TypeId::of::<Perlin>(), TextureViewDimension::D3, NoiseOp::Generator),
+                (TypeId::of::<Invert>(), TextureViewDimension::D3, NoiseOp::Modifier),
+            ],
+        );
+
+        // Modifiers on cubemaps run on the faces bound as a 2D array.
+        noise_queue.warm_up.clear();
+        assert_eq!(noise_queue.warm_up((Perlin::default(), Invert), TextureViewDimension::Cube), Ok(()));
+        assert_eq!(
+            noise_queue.warm_up.iter().map(|(key, _)| key.dimension).collect::<Vec<_>>(),
+            vec![TextureViewDimension::Cube, TextureViewDimension::D2Array],
+        );
+    }
+
+    #[test]
+    fn warm_up_is_not_ready_in_the_same_frame() {
+        use bevy::reflect::TypePath;
+        use crate::noise::generators::Perlin;
+
+        let mut noise_queue = ComputeNoiseQueue::default();
+        noise_queue.register::<Perlin>();
+        noise_queue.warm_up_compiled = true;
+        assert!(noise_queue.pipelines_ready());
+
+        noise_queue.warm_up(Perlin::default(), TextureViewDimension::D2).unwrap();
+        assert!(!noise_queue.pipelines_ready());
+
+        noise_queue.warm_up.clear();
+        noise_queue.warm_up_failed.push((Perlin::type_path(), TextureViewDimension::D2));
+        assert!(!noise_queue.pipelines_ready());
+        assert_eq!(noise_queue.failed_pipelines(), [(Perlin::type_path(), TextureViewDimension::D2)]);
+    }
+
+    #[test]
+    fn warm_up_rejects_2d_only_noise_on_3d() {
+        use bevy::reflect::TypePath;
+        use crate::noise::modifiers::{erosion::ErosionPass, ThermalErosion};
+
+        let mut noise_queue = ComputeNoiseQueue::default();
+        noise_queue.register::<ErosionPass>();
+        assert_eq!(
+            noise_queue.warm_up(ThermalErosion::default(), TextureViewDimension::D3),
+            Err(ComputeNoiseError::Unsupported3d(ErosionPass::type_path())),
+        );
+        assert!(noise_queue.warm_up.is_empty());
+        assert_eq!(noise_queue.warm_up(ThermalErosion::default(), TextureViewDimension::D2Array), Ok(()));
+    }
+
+    #[test]
+    fn queue_region_rejects_multi_pass_noise() {
+        use bevy::reflect::TypePath;
+        use crate::noise::{generators::Perlin, modifiers::{morphology::MorphologyPass, Morphology}};
+
+        let mut noise_queue = ComputeNoiseQueue::default();
+        noise_queue.register::<Perlin>();
+        noise_queue.register::<MorphologyPass>();
+
+        let region = ComputeNoiseRegion::rect(UVec2::new(4, 4), UVec2::new(8, 8));
+        assert_eq!(noise_queue.queue_region(Handle::default(), region, Perlin::default()), Ok(()));
+        assert_eq!(
+            noise_queue.queue_region(Handle::default(), region, (Perlin::default(), Morphology::default())),
+            Err(ComputeNoiseError::UnsupportedRegion(MorphologyPass::type_path())),
+        );
+        assert_eq!(noise_queue.queue.len(), 1);
+        assert_eq!(noise_queue.queue(Handle::default(), Morphology::default()), Ok(()));
+    }
 }
@@ -0,0 +1,74 @@
+use std::marker::PhantomData;
+
+use bevy::{prelude::*, reflect::{GetTypeRegistration, Typed}};
+
+use crate::{noise::generators::expression::prepare_expression_shaders, noise_queue::{ComputeNoiseQueue, IntoNoiseSequence}};
+
+/// Noise that can be stored in a [`NoiseTexture`], any reflectable [`IntoNoiseSequence`] like
+/// `Perlin` or a tuple such as `(Fbm<Perlin>, Invert)`.
+pub trait NoiseStack: IntoNoiseSequence + Clone + Default + FromReflect + TypePath + GetTypeRegistration + Typed + Send + Sync + 'static {}
+
+impl<T: IntoNoiseSequence + Clone + Default + FromReflect + TypePath + GetTypeRegistration + Typed + Send + Sync + 'static> NoiseStack for T {}
+
+/// Keeps `image` filled with `noise`, queuing it again whenever the component changes,
+/// so editing it in an inspector regenerates the texture. Needs a [`NoiseTexturePlugin<T>`].
+///
+/// ```ignore
+/// commands.spawn(NoiseTexture { image, noise: (Fbm::<Perlin>::default(), Invert) });
+/// ```
+#[derive(Component, Reflect, Clone, Default)]
+#[reflect(Component, Default)]
+pub struct NoiseTexture<T: NoiseStack> {
+    pub image: Handle<Image>,
+    pub noise: T,
+}
+
+/// Registers [`NoiseTexture<T>`] for reflection and regenerates it when it changes.
+#[derive(Default)]
+pub struct NoiseTexturePlugin<T: NoiseStack>(PhantomData<T>);
+
+impl<T: NoiseStack> Plugin for NoiseTexturePlugin<T> {
+    fn build(&self, app: &mut App) {
+        app
+            .register_type::<NoiseTexture<T>>()
+            .add_systems(PostUpdate, queue_noise_textures::<T>.before(prepare_expression_shaders));
+    }
+}
+
+fn queue_noise_textures<T: NoiseStack>(
+    textures: Query<&NoiseTexture<T>, Changed<NoiseTexture<T>>>,
+    mut noise_queue: ResMut<ComputeNoiseQueue>,
+) {
+    for texture in &textures {
+        if let Err(error) = noise_queue.queue(texture.image.clone(), texture.noise.clone()) {
+            error!("{error}");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn requeues_when_edited() {
+        use crate::noise::{generators::Perlin, modifiers::Invert};
+
+        let mut app = App::new();
+        app
+            .init_resource::<ComputeNoiseQueue>()
+            .add_systems(Update, queue_noise_textures::<(Perlin, Invert)>);
+        let mut noise_queue = app.world_mut().resource_mut::<ComputeNoiseQueue>();
+        noise_queue.register::<Perlin>();
+        noise_queue.register::<Invert>();
+
+        let entity = app.world_mut().spawn(NoiseTexture::<(Perlin, Invert)>::default()).id();
+        app.update();
+        app.update();
+        assert_eq!(app.world().resource::<ComputeNoiseQueue>().queue.len(), 1);
+
+        app.world_mut().get_mut::<NoiseTexture<(Perlin, Invert)>>(entity).unwrap().noise.0.seed = 1;
+        app.update();
+        assert_eq!(app.world().resource::<ComputeNoiseQueue>().queue.len(), 2);
+    }
+}
@@ -0,0 +1,121 @@
+use bevy::{
+    prelude::*,
+    render::{
+        render_asset::RenderAssets,
+        render_resource::{BufferDescriptor, BufferUsages, MapMode},
+        renderer::RenderDevice,
+        texture::GpuImage,
+        MainWorld,
+    },
+};
+
+use crate::{
+    image::ComputeNoiseSize,
+    noise_queue::{ComputeNoiseQueue, ComputeNoiseRenderQueue},
+    render::compute::{ComputeNoiseDispatches, TextureReadback},
+};
+
+/// Called with the image's `Rgba8Unorm` texels, slices and layers one after another, or `None` if mapping failed.
+pub(crate) type ReadbackCallback = Box<dyn FnOnce(ComputeNoiseSize, Option<Vec<u8>>) + Send + Sync>;
+
+pub(crate) struct ReadbackRequest {
+    image: Handle<Image>,
+    callback: ReadbackCallback,
+}
+
+// Main World
+/// Images to copy back from the GPU once the noise queued into them has been written.
+#[derive(Resource, Default)]
+pub(crate) struct ComputeNoiseReadbackQueue {
+    requested: Vec<ReadbackRequest>,
+}
+
+impl ComputeNoiseReadbackQueue {
+    /// `callback` runs on the render thread, so it should hand the data off rather than process it.
+    pub fn read(&mut self, image: Handle<Image>, callback: impl FnOnce(ComputeNoiseSize, Option<Vec<u8>>) + Send + Sync + 'static) {
+        self.requested.push(ReadbackRequest { image, callback: Box::new(callback) });
+    }
+}
+
+// Render World
+#[derive(Resource, Default)]
+pub(crate) struct ComputeNoiseReadbacks {
+    /// Requests waiting on noise that's still being written to their image.
+    waiting: Vec<(ReadbackRequest, ComputeNoiseSize)>,
+    /// Requests copied this frame, mapped once the copies have been submitted.
+    copied: Vec<(ReadbackRequest, ComputeNoiseSize, TextureReadback)>,
+}
+
+pub(crate) fn extract_compute_noise_readbacks(
+    mut world: ResMut<MainWorld>,
+    mut readbacks: ResMut<ComputeNoiseReadbacks>,
+) {
+    world.resource_scope(|world, mut readback_queue: Mut<ComputeNoiseReadbackQueue>| {
+        let noise_queue = world.resource::<ComputeNoiseQueue>();
+        let images = world.resource::<Assets<Image>>();
+
+        for request in std::mem::take(&mut readback_queue.requested) {
+            // Noise waiting on its images to load hasn't reached the render world yet.
+            match images.get(&request.image) {
+                Some(image) if !noise_queue.is_writing(&request.image) => {
+                    readbacks.waiting.push((request, ComputeNoiseSize::from(image)));
+                },
+                _ => readback_queue.requested.push(request),
+            }
+        }
+    });
+}
+
+pub(crate) fn prepare_compute_noise_readbacks(
+    mut readbacks: ResMut<ComputeNoiseReadbacks>,
+    mut dispatches: ResMut<ComputeNoiseDispatches>,
+    render_queue: Res<ComputeNoiseRenderQueue>,
+    gpu_images: Res<RenderAssets<GpuImage>>,
+    render_device: Res<RenderDevice>,
+) {
+    let readbacks = &mut *readbacks;
+    for (request, size) in std::mem::take(&mut readbacks.waiting) {
+        let gpu_image = gpu_images.get(&request.image);
+        // The copy is recorded after this frame's dispatches, so only noise left for later frames has to be waited on.
+        let Some(gpu_image) = gpu_image.filter(|_| !render_queue.is_writing(request.image.id())) else {
+            readbacks.waiting.push((request, size));
+            continue;
+        };
+
+        let extent = size.into();
+        let bytes_per_row = RenderDevice::align_copy_bytes_per_row(size.width() as usize * 4) as u32;
+        let buffer = render_device.create_buffer(&BufferDescriptor {
+            label: Some("compute_noise_readback_buffer"),
+            size: bytes_per_row as u64 * size.height() as u64 * (size.depth() * size.layers()) as u64,
+            usage: BufferUsages::MAP_READ | BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        let readback = TextureReadback { texture: gpu_image.texture.clone(), buffer, size: extent, bytes_per_row };
+        dispatches.readbacks.push(readback.clone());
+        readbacks.copied.push((request, size, readback));
+    }
+}
+
+pub(crate) fn map_compute_noise_readbacks(mut readbacks: ResMut<ComputeNoiseReadbacks>) {
+    for (request, size, readback) in readbacks.copied.drain(..) {
+        let buffer = readback.buffer.clone();
+        readback.buffer.slice(..).map_async(MapMode::Read, move |result| {
+            if let Err(error) = result {
+                error!("Failed to read back compute noise: {error}");
+                return (request.callback)(size, None);
+            }
+
+            // Copies pad rows out to `COPY_BYTES_PER_ROW_ALIGNMENT`.
+            let row_len = size.width() as usize * 4;
+            let data = buffer.slice(..).get_mapped_range()
+                .chunks(readback.bytes_per_row as usize)
+                .flat_map(|row| &row[..row_len])
+                .copied()
+                .collect();
+            buffer.unmap();
+
+            (request.callback)(size, Some(data));
+        });
+    }
+}
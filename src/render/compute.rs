@@ -1,6 +1,235 @@
-use bevy::{prelude::*, render::{render_resource::{CommandEncoder, CommandEncoderDescriptor, ComputePassDescriptor, PipelineCache}, renderer::{RenderDevice, RenderQueue}}};
+use std::{cmp::Reverse, num::NonZeroU64, sync::Arc};
 
-use crate::noise_queue::ComputeNoiseRenderQueue;
+use bevy::{prelude::*, render::{render_resource::{BindGroup, BindGroupEntries, Buffer, BufferBinding, BufferDescriptor, BufferUsages, CachedComputePipelineId, CommandEncoder, CommandEncoderDescriptor, ComputePassDescriptor, Extent3d, ImageCopyBuffer, ImageDataLayout, PipelineCache, Texture}, renderer::{RenderDevice, RenderQueue}}};
+
+use bytemuck::{Pod, Zeroable};
+
+use crate::{image::{ComputeNoiseRegion, ComputeNoiseSize}, noise_queue::ComputeNoiseRenderQueue, render::pipeline::{pipeline_compile_error, ComputeNoisePipeline, ComputeNoisePipelineKey}, settings::{ComputeNoiseDispatchMode, ComputeNoiseSettings}, terrain::gpu::PreparedDisplacement};
+
+/// Region of the texture covered by a single dispatch, bound at group 1 of every noise shader.
+#[derive(Clone, Copy, Default, Pod, Zeroable)]
+#[repr(C)]
+pub struct ComputeNoiseDispatch {
+    offset: [u32; 3],
+    layer: u32,
+    end: [u32; 3],
+    _padding2: u32,
+}
+
+struct PreparedDispatch {
+    pipeline_id: CachedComputePipelineId,
+    bind_group: BindGroup,
+    /// Offset of this dispatch's region in the dispatch buffer.
+    offset: u32,
+    workgroups: (u32, u32, u32),
+}
+
+/// Copy of a whole texture into a buffer, recorded after the frame's dispatches.
+#[derive(Clone)]
+pub(crate) struct TextureReadback {
+    pub texture: Texture,
+    pub buffer: Buffer,
+    pub size: Extent3d,
+    pub bytes_per_row: u32,
+}
+
+/// Dispatches that fit in this frame's budget, recorded by whichever of [`compute_noise`] or
+/// [`ComputeNoiseNode`](super::node::ComputeNoiseNode) matches [`ComputeNoiseDispatchMode`].
+#[derive(Resource, Default)]
+pub struct ComputeNoiseDispatches {
+    dispatches: Vec<PreparedDispatch>,
+    bind_group: Option<BindGroup>,
+    /// Every dispatch region of the frame, each at an offset aligned for use as a dynamic uniform.
+    buffer: Option<Buffer>,
+    /// Leases of pooled buffers used by the dispatches, kept until next frame by which point they've been submitted.
+    leases: Vec<Arc<()>>,
+    pub(crate) readbacks: Vec<TextureReadback>,
+    pub(crate) terrain: Vec<PreparedDisplacement>,
+    /// Noise dropped this frame because its pipeline failed to compile, with the error.
+    pub(crate) failed: Vec<(ComputeNoisePipelineKey, String)>,
+}
+
+impl ComputeNoiseDispatches {
+    pub fn is_empty(&self) -> bool {
+        self.dispatches.is_empty() && self.readbacks.is_empty() && self.terrain.is_empty()
+    }
+
+    pub fn encode(&self, pipeline_cache: &PipelineCache, encoder: &mut CommandEncoder) {
+        if let Some(dispatch_bind_group) = &self.bind_group {
+            let mut pass = encoder.begin_compute_pass(&ComputePassDescriptor {
+                label: Some("compute_noise_pass"),
+                ..default()
+            });
+            for dispatch in &self.dispatches {
+                let Some(pipeline) = pipeline_cache.get_compute_pipeline(dispatch.pipeline_id) else { continue };
+                pass.set_pipeline(pipeline);
+                pass.set_bind_group(0, &dispatch.bind_group, &[]);
+                pass.set_bind_group(1, dispatch_bind_group, &[dispatch.offset]);
+                pass.dispatch_workgroups(dispatch.workgroups.0, dispatch.workgroups.1, dispatch.workgroups.2);
+            }
+        }
+
+        for displacement in &self.terrain {
+            let Some(pipeline) = pipeline_cache.get_compute_pipeline(displacement.pipeline_id) else { continue };
+            {
+                let mut pass = encoder.begin_compute_pass(&ComputePassDescriptor {
+                    label: Some("terrain_displacement_pass"),
+                    ..default()
+                });
+                pass.set_pipeline(pipeline);
+                pass.set_bind_group(0, &displacement.bind_group, &[]);
+                pass.dispatch_workgroups(displacement.workgroups, 1, 1);
+            }
+            encoder.copy_buffer_to_buffer(&displacement.output, 0, &displacement.vertex_buffer, displacement.vertex_offset, displacement.output.size());
+        }
+
+        for readback in &self.readbacks {
+            encoder.copy_texture_to_buffer(
+                readback.texture.as_image_copy(),
+                ImageCopyBuffer {
+                    buffer: &readback.buffer,
+                    layout: ImageDataLayout {
+                        offset: 0,
+                        bytes_per_row: Some(readback.bytes_per_row),
+                        rows_per_image: Some(readback.size.height),
+                    },
+                },
+                readback.size,
+            );
+        }
+    }
+}
+
+pub fn prepare_compute_noise_dispatches(
+    mut dispatches: ResMut<ComputeNoiseDispatches>,
+    mut compute_noise_queue: ResMut<ComputeNoiseRenderQueue>,
+    mut pipeline_cache: ResMut<PipelineCache>,
+    pipeline: Res<ComputeNoisePipeline>,
+    settings: Res<ComputeNoiseSettings>,
+    render_device: Res<RenderDevice>,
+    render_queue: Res<RenderQueue>,
+) {
+    dispatches.dispatches.clear();
+    dispatches.bind_group = None;
+    dispatches.leases.clear();
+    dispatches.readbacks.clear();
+    dispatches.terrain.clear();
+    dispatches.failed.clear();
+
+    if compute_noise_queue.queue.is_empty() { return; }
+
+    pipeline_cache.process_queue();
+
+    // Sequences would wait forever on a pipeline that failed to compile, the pipeline cache has already logged why.
+    compute_noise_queue.queue.retain(|sequence| {
+        let Some((key, error)) = sequence.iter().find_map(|render_noise| {
+            pipeline_compile_error(&pipeline_cache, render_noise.pipeline_id).map(|error| (render_noise.key, error))
+        }) else { return true };
+        dispatches.failed.push((key, error.to_string()));
+        false
+    });
+
+    // Stable, so sequences with the same priority are dispatched in the order they were queued.
+    compute_noise_queue.queue.sort_by_key(|sequence| Reverse(sequence[0].priority));
+
+    // Work out which slabs fit in this frame's budget before recording anything,
+    // all the dispatch regions need to be in the uniform buffer before it's bound.
+    let mut regions = Vec::new();
+    let mut texels = 0;
+    'sequences: for sequence in compute_noise_queue.queue.iter_mut() {
+        if !sequence.iter().all(|render_noise| {
+            pipeline_cache.get_compute_pipeline(render_noise.pipeline_id).is_some()
+        }) {
+            continue;
+        }
+
+        for render_noise in sequence.iter_mut() {
+            let size = render_noise.size;
+            let (start, end) = ComputeNoiseRegion::bounds(render_noise.region, size);
+            let slices = render_noise.slices();
+            render_noise.next_z = render_noise.next_z.max(slices.start);
+            while render_noise.next_z < slices.end {
+                let slab_start = render_noise.next_z;
+                // Layers are dispatched one at a time, 3D textures in slabs.
+                let slab_end = if size.is_layered() { slab_start + 1 } else { settings.slab_end(slab_start, end.z) };
+                let slab_texels = (end.x - start.x) as u64 * (end.y - start.y) as u64 * (slab_end - slab_start) as u64;
+
+                let over_budget = settings.texel_budget.is_some_and(|budget| texels + slab_texels > budget)
+                    || settings.dispatch_budget.is_some_and(|budget| regions.len() as u32 >= budget);
+                if over_budget && !regions.is_empty() { break 'sequences; }
+
+                texels += slab_texels;
+                render_noise.next_z = slab_end;
+
+                let (workgroups, region) = match size {
+                    ComputeNoiseSize::D3(..) => (
+                        ComputeNoiseSize::D3(end.x - start.x, end.y - start.y, slab_end - slab_start).workgroup_count(),
+                        ComputeNoiseDispatch { offset: [start.x, start.y, slab_start], end: [end.x, end.y, slab_end], ..default() },
+                    ),
+                    _ => (
+                        ComputeNoiseSize::D2(end.x - start.x, end.y - start.y).workgroup_count(),
+                        ComputeNoiseDispatch {
+                            offset: [start.x, start.y, 0],
+                            end: [end.x, end.y, 1],
+                            layer: if size.is_layered() { slab_start } else { 0 },
+                            ..default()
+                        },
+                    ),
+                };
+                dispatches.dispatches.push(PreparedDispatch {
+                    pipeline_id: render_noise.pipeline_id,
+                    bind_group: render_noise.bind_group.clone(),
+                    offset: 0,
+                    workgroups,
+                });
+                regions.push(region);
+            }
+
+            if render_noise.frame_break { break; }
+        }
+    }
+
+    // Instructions stay in the queue until every slab or layer has been dispatched.
+    for sequence in compute_noise_queue.queue.iter_mut() {
+        let finished = sequence.iter()
+            .take_while(|render_noise| render_noise.next_z >= render_noise.slices().end)
+            .count();
+        dispatches.leases.extend(sequence.drain(..finished).flat_map(|render_noise| render_noise.leases));
+    }
+    compute_noise_queue.queue.retain(|sequence| !sequence.is_empty());
+
+    if regions.is_empty() { return; }
+
+    let stride = std::mem::size_of::<ComputeNoiseDispatch>()
+        .next_multiple_of(render_device.limits().min_uniform_buffer_offset_alignment as usize);
+    let mut contents = vec![0; stride * regions.len()];
+    for (i, (region, dispatch)) in regions.iter().zip(dispatches.dispatches.iter_mut()).enumerate() {
+        contents[i * stride..][..std::mem::size_of::<ComputeNoiseDispatch>()].copy_from_slice(bytemuck::bytes_of(region));
+        dispatch.offset = (i * stride) as u32;
+    }
+
+    let buffer = dispatches.buffer
+        .take()
+        .filter(|buffer| buffer.size() >= contents.len() as u64)
+        .unwrap_or_else(|| render_device.create_buffer(&BufferDescriptor {
+            label: Some("compute_noise_dispatch_buffer"),
+            size: contents.len() as u64,
+            usage: BufferUsages::UNIFORM | BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        }));
+    render_queue.write_buffer(&buffer, 0, &contents);
+
+    dispatches.bind_group = Some(render_device.create_bind_group(
+        "compute_noise_dispatch_bind_group",
+        &pipeline.dispatch_layout,
+        &BindGroupEntries::single(BufferBinding {
+            buffer: &buffer,
+            offset: 0,
+            size: NonZeroU64::new(std::mem::size_of::<ComputeNoiseDispatch>() as u64),
+        }),
+    ));
+    dispatches.buffer = Some(buffer);
+}
 
 #[derive(Resource)]
 pub struct ComputeNoiseEncoder {
@@ -23,41 +252,16 @@ impl FromWorld for ComputeNoiseEncoder {
 
 pub fn compute_noise(
     mut compute_noise_encoder: ResMut<ComputeNoiseEncoder>,
-    mut compute_noise_queue: ResMut<ComputeNoiseRenderQueue>,
-    mut pipeline_cache: ResMut<PipelineCache>,
+    dispatches: Res<ComputeNoiseDispatches>,
+    pipeline_cache: Res<PipelineCache>,
+    settings: Res<ComputeNoiseSettings>,
 ) {
-    if compute_noise_queue.queue.is_empty() { return; }
-
-    pipeline_cache.process_queue();
+    if settings.mode != ComputeNoiseDispatchMode::Encoder || dispatches.is_empty() { return; }
 
     let Some(encoder) = &mut compute_noise_encoder.encoder else { return error!("Encoder is None") };
-    let mut pass = encoder.begin_compute_pass(&ComputePassDescriptor::default());
-    
-    let mut dispatched = Vec::new();
-    for (seq_idx, sequence) in compute_noise_queue.queue.iter().enumerate() {
-        if sequence.iter().all(|render_noise| {
-            pipeline_cache.get_compute_pipeline(render_noise.pipeline_id).is_some()
-        }) {
-            for render_noise in sequence {
-                let pipeline = pipeline_cache.get_compute_pipeline(render_noise.pipeline_id).unwrap();
-                pass.set_pipeline(pipeline);
-                pass.set_bind_group(0, &render_noise.bind_group, &[]);
-    
-                let workgroups = render_noise.size.workgroup_count();
-                pass.dispatch_workgroups(workgroups.0, workgroups.1, workgroups.2);
-            }
-            dispatched.push(seq_idx);
-
-        }
-    }
+    dispatches.encode(&pipeline_cache, encoder);
 
-    for &i in dispatched.iter().rev() {
-        compute_noise_queue.queue.remove(i);
-    }
-
-    if !dispatched.is_empty() {
-        compute_noise_encoder.submit = true;
-    }
+    compute_noise_encoder.submit = true;
 }
 
 pub fn submit_compute_noise(
@@ -73,4 +277,4 @@ pub fn submit_compute_noise(
         }));
         compute_noise_encoder.submit = false;
     }
-}
\ No newline at end of file
+}
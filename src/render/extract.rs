@@ -1,6 +1,6 @@
 use bevy::{prelude::*, render::MainWorld};
 
-use crate::noise_queue::ComputeNoiseBufferQueue;
+use crate::{noise_queue::{ComputeNoiseBufferQueue, ComputeNoisePipelinesReady, ComputeNoiseQueue}, render::pipeline::ComputeNoiseWarmUp};
 
 pub(crate) fn extract_compute_noise_queue(
     mut render_commands: Commands,
@@ -9,4 +9,17 @@ pub(crate) fn extract_compute_noise_queue(
     let mut main_compute_noise_queue = world.resource_mut::<ComputeNoiseBufferQueue>();
     render_commands.insert_resource(main_compute_noise_queue.clone());
     main_compute_noise_queue.queue.clear();
-}
\ No newline at end of file
+}
+
+pub(crate) fn extract_compute_noise_warm_up(
+    mut world: ResMut<MainWorld>,
+    mut warm_up: ResMut<ComputeNoiseWarmUp>,
+) {
+    let mut noise_queue = world.resource_mut::<ComputeNoiseQueue>();
+    warm_up.pending.append(&mut noise_queue.warm_up);
+    noise_queue.warm_up_failed.append(&mut warm_up.failed);
+    noise_queue.warm_up_compiled = warm_up.is_ready();
+
+    let ready = ComputeNoisePipelinesReady(noise_queue.pipelines_ready());
+    world.resource_mut::<ComputeNoisePipelinesReady>().set_if_neq(ready);
+}
@@ -1,4 +1,5 @@
 pub mod extract;
 pub mod prepare;
 pub mod pipeline;
-pub mod compute;
\ No newline at end of file
+pub mod compute;
+pub mod node;
\ No newline at end of file
@@ -0,0 +1,42 @@
+use bevy::{
+    prelude::*,
+    render::{
+        render_graph::{Node, NodeRunError, RenderGraphContext, RenderLabel},
+        render_resource::PipelineCache,
+        renderer::RenderContext,
+    },
+};
+
+use crate::settings::{ComputeNoiseDispatchMode, ComputeNoiseSettings};
+
+use super::compute::ComputeNoiseDispatches;
+
+#[derive(Debug, Hash, PartialEq, Eq, Clone, RenderLabel)]
+pub struct ComputeNoiseLabel;
+
+/// Records the frame's noise dispatches into the render graph when using [`ComputeNoiseDispatchMode::RenderGraph`].
+///
+/// The node runs before the camera driver, add edges to [`ComputeNoiseLabel`] to order other
+/// compute work that reads the noise in the same frame.
+#[derive(Default)]
+pub struct ComputeNoiseNode;
+
+impl Node for ComputeNoiseNode {
+    fn run<'w>(
+        &self,
+        _graph: &mut RenderGraphContext,
+        render_context: &mut RenderContext<'w>,
+        world: &'w World,
+    ) -> Result<(), NodeRunError> {
+        if world.resource::<ComputeNoiseSettings>().mode != ComputeNoiseDispatchMode::RenderGraph {
+            return Ok(());
+        }
+
+        let dispatches = world.resource::<ComputeNoiseDispatches>();
+        if !dispatches.is_empty() {
+            dispatches.encode(world.resource::<PipelineCache>(), render_context.command_encoder());
+        }
+
+        Ok(())
+    }
+}
@@ -1,136 +1,283 @@
-use std::any::TypeId;
+use std::{any::TypeId, num::NonZeroU64};
 
-use bevy::{prelude::*, render::{render_resource::{binding_types::{texture_storage_2d, uniform_buffer_sized}, BindGroupLayout, BindGroupLayoutEntries, BindGroupLayoutEntryBuilder, BindingType, ComputePipelineDescriptor, IntoBindGroupLayoutEntryBuilder, ShaderDefVal, ShaderRef, ShaderStages, SpecializedComputePipeline, StorageTextureAccess, TextureDimension, TextureFormat, TextureViewDimension}, renderer::RenderDevice}, utils::HashMap};
+use bevy::{prelude::*, reflect::TypePath, render::{render_resource::{binding_types::{storage_buffer_read_only_sized, storage_buffer_sized, texture_storage_2d, texture_storage_2d_array, uniform_buffer_sized}, BindGroupLayout, BindGroupLayoutEntry, BindGroupLayoutEntries, BindGroupLayoutEntryBuilder, BindingType, CachedComputePipelineId, CachedPipelineState, ComputePipelineDescriptor, IntoBindGroupLayoutEntryBuilder, PipelineCache, PipelineCacheError, ShaderDefVal, ShaderRef, ShaderStages, SpecializedComputePipeline, SpecializedComputePipelines, StorageTextureAccess, TextureFormat, TextureViewDimension}, renderer::RenderDevice}, utils::HashMap};
 
-use crate::noise::{generators::{ComputeNoiseGenerator, Fbm}, modifiers::ComputeNoiseModifier};
+use crate::{error::ComputeNoiseError, render::compute::ComputeNoiseDispatch, noise::{generators::{blue_noise::BlueNoisePass, expression::{ExpressionNoise, ExpressionShaders}, CloudDetail, CloudShape, ComputeNoiseGenerator, Fbm, Spherical, White, WrapperShaders}, modifiers::ComputeNoiseModifier}};
 
-pub fn load_generator_shader<T: ComputeNoiseGenerator>(world: &mut World) {
-    let shader_2d = match T::shader_2d() {
-        ShaderRef::Default => None,
-        ShaderRef::Handle(handle) => Some(handle),
-        ShaderRef::Path(path) => Some(world.resource::<AssetServer>().load(path)),
-    }.unwrap();
-
-    let shader_3d = match T::shader_3d() {
-        ShaderRef::Default => None,
+fn load_shader<T: TypePath>(world: &World, shader: ShaderRef) -> Option<Handle<Shader>> {
+    match shader {
+        ShaderRef::Default => {
+            error!("{}", ComputeNoiseError::MissingShader(T::type_path()));
+            None
+        },
         ShaderRef::Handle(handle) => Some(handle),
         ShaderRef::Path(path) => Some(world.resource::<AssetServer>().load(path)),
-    }.unwrap();
+    }
+}
 
+/// Cubes sample the generator's 3D noise function through a wrapper, so they need `wrappers`.
+pub(crate) fn load_generator_shader<T: ComputeNoiseGenerator>(world: &mut World, wrappers: Option<&WrapperShaders>) {
+    let (Some(shader_2d), Some(shader_3d)) = (
+        load_shader::<T>(world, T::shader_2d()),
+        load_shader::<T>(world, T::shader_3d()),
+    ) else { return };
     let mut pipeline = world.resource_mut::<ComputeNoisePipeline>();
 
-    pipeline.shaders.insert(
-        ComputeNoisePipelineKey {
-            type_id: TypeId::of::<T>(),
-            dimension: TextureDimension::D2,
-            op: NoiseOp::Generator,
-        },
-        shader_2d,
+    let type_id = TypeId::of::<T>();
+    pipeline.register_generator(type_id, 0, &shader_2d, &[(TextureViewDimension::D2, &[]), (TextureViewDimension::D2Array, &["ARRAY"])], None);
+    pipeline.register_generator(type_id, 0, &shader_3d, &[(TextureViewDimension::D3, &[])], None);
+    // Cubes sample the generator's 3D noise function, the same way fbm does.
+    if let Some(wrappers) = wrappers {
+        pipeline.register_generator(type_id, 0, &wrappers.cube, &[(TextureViewDimension::Cube, &["ARRAY"])], None);
+    }
+}
+
+pub(crate) fn load_fbm_shaders<T: ComputeNoiseGenerator>(world: &mut World, wrappers: &WrapperShaders) {
+    world.resource_mut::<ComputeNoisePipeline>().register_generator(
+        TypeId::of::<Fbm<T>>(),
+        0,
+        &wrappers.fbm,
+        &[
+            (TextureViewDimension::D2, &["2D"]),
+            (TextureViewDimension::D2Array, &["2D", "ARRAY"]),
+            (TextureViewDimension::D3, &["3D"]),
+            (TextureViewDimension::Cube, &["CUBE", "ARRAY"]),
+        ],
+        None,
     );
+}
 
-    pipeline.shaders.insert(
-        ComputeNoisePipelineKey {
-            type_id: TypeId::of::<T>(),
-            dimension: TextureDimension::D3,
-            op: NoiseOp::Generator,
-        },
-        shader_3d,
+/// Spherical noise writes 2D textures, as an equirectangular projection or one texel per vertex, and cube faces.
+pub(crate) fn load_spherical_shaders<T: ComputeNoiseGenerator>(world: &mut World, wrappers: &WrapperShaders) {
+    let render_device = world.resource::<RenderDevice>();
+    let layout = |texture| render_device.create_bind_group_layout(
+        "spherical_noise_layout",
+        &BindGroupLayoutEntries::sequential(
+            ShaderStages::COMPUTE,
+            (
+                texture,
+                uniform_buffer_sized(false, None),
+                storage_buffer_read_only_sized(false, None),
+            )
+        )
+    );
+    let (layout_2d, layout_array) = (layout(noise_texture_2d()), layout(noise_texture_2d_array()));
+
+    let mut pipeline = world.resource_mut::<ComputeNoisePipeline>();
+    let type_id = TypeId::of::<Spherical<T>>();
+    pipeline.register_generator(type_id, 0, &wrappers.spherical, &[(TextureViewDimension::D2, &[])], Some(&layout_2d));
+    pipeline.register_generator(
+        type_id,
+        0,
+        &wrappers.spherical,
+        &[(TextureViewDimension::D2Array, &["ARRAY"]), (TextureViewDimension::Cube, &["CUBE", "ARRAY"])],
+        Some(&layout_array),
     );
 }
 
-pub fn load_fbm_shaders<T: ComputeNoiseGenerator>(world: &mut World) {
-    let shader = world.resource::<AssetServer>().load("embedded://bevy_compute_noise/noise/generators/shaders/fbm.wgsl");
+/// Cloud noise is only written to 3D textures, the shape and detail textures share a shader.
+pub(crate) fn load_cloud_shaders(world: &mut World) {
+    let shader: Handle<Shader> = world.resource::<AssetServer>().load("embedded://bevy_compute_noise/noise/generators/shaders/cloud.wgsl");
     let mut pipeline = world.resource_mut::<ComputeNoisePipeline>();
 
-    pipeline.shaders.insert(
-        ComputeNoisePipelineKey {
-            type_id: TypeId::of::<Fbm<T>>(),
-            dimension: TextureDimension::D2,
-            op: NoiseOp::Generator,
-        },
-        shader.clone(),
-    );
+    pipeline.register_generator(TypeId::of::<CloudShape>(), 0, &shader, &[(TextureViewDimension::D3, &[])], None);
+    pipeline.register_generator(TypeId::of::<CloudDetail>(), 0, &shader, &[(TextureViewDimension::D3, &["DETAIL"])], None);
+}
 
-    pipeline.shaders.insert(
-        ComputeNoisePipelineKey {
-            type_id: TypeId::of::<Fbm<T>>(),
-            dimension: TextureDimension::D3,
-            op: NoiseOp::Generator,
-        },
-        shader,
+/// White noise is written to 2D, 2D array and 3D textures with the same shader.
+pub(crate) fn load_white_shaders(world: &mut World) {
+    let shader: Handle<Shader> = world.resource::<AssetServer>().load("embedded://bevy_compute_noise/noise/generators/shaders/white.wgsl");
+    world.resource_mut::<ComputeNoisePipeline>().register_generator(
+        TypeId::of::<White>(),
+        0,
+        &shader,
+        &[(TextureViewDimension::D2, &[]), (TextureViewDimension::D2Array, &["ARRAY"]), (TextureViewDimension::D3, &["3D"])],
+        None,
     );
+}
 
-    pipeline.shader_defs.insert(
-        ComputeNoisePipelineKey {
-            type_id: TypeId::of::<Fbm<T>>(),
-            dimension: TextureDimension::D2,
-            op: NoiseOp::Generator,
-        },
-        vec![T::shader_def(), "2D".into()]
-    );
-    pipeline.shader_defs.insert(
-        ComputeNoisePipelineKey {
-            type_id: TypeId::of::<Fbm<T>>(),
-            dimension: TextureDimension::D3,
-            op: NoiseOp::Generator,
-        },
-        vec![T::shader_def(), "3D".into()]
+/// Blue noise is only written to 2D and 2D array textures, every pass binds the values it relaxes after the uniform.
+pub(crate) fn load_blue_noise_shaders(world: &mut World) {
+    let shader: Handle<Shader> = world.resource::<AssetServer>().load("embedded://bevy_compute_noise/noise/generators/shaders/blue_noise.wgsl");
+    let render_device = world.resource::<RenderDevice>();
+    let layout = |texture| render_device.create_bind_group_layout(
+        "blue_noise_layout",
+        &BindGroupLayoutEntries::sequential(
+            ShaderStages::COMPUTE,
+            (
+                texture,
+                uniform_buffer_sized(false, None),
+                storage_buffer_read_only_sized(false, None),
+                storage_buffer_sized(false, None),
+            )
+        )
     );
+    let (layout_2d, layout_array) = (layout(noise_texture_2d()), layout(noise_texture_2d_array()));
+
+    let mut pipeline = world.resource_mut::<ComputeNoisePipeline>();
+    let type_id = TypeId::of::<BlueNoisePass>();
+    pipeline.register_generator(type_id, 0, &shader, &[(TextureViewDimension::D2, &[])], Some(&layout_2d));
+    pipeline.register_generator(type_id, 0, &shader, &[(TextureViewDimension::D2Array, &["ARRAY"])], Some(&layout_array));
+}
+
+/// Adds pipelines for expressions queued since last frame, each is its own variant of [`ExpressionNoise`].
+pub(crate) fn load_expression_shaders(
+    mut pipeline: ResMut<ComputeNoisePipeline>,
+    expression_shaders: Res<ExpressionShaders>,
+    render_device: Res<RenderDevice>,
+    mut layouts: Local<Option<(BindGroupLayout, BindGroupLayout, BindGroupLayout)>>,
+) {
+    if !expression_shaders.is_changed() { return; }
+
+    let (layout_2d, layout_3d, layout_array) = layouts.get_or_insert_with(|| {
+        let layout = |label, texture| render_device.create_bind_group_layout(
+            label,
+            &BindGroupLayoutEntries::sequential(ShaderStages::COMPUTE, (texture, storage_buffer_read_only_sized(false, None))),
+        );
+        (
+            layout("expression_noise_2d_layout", noise_texture_2d()),
+            layout("expression_noise_3d_layout", noise_texture_3d()),
+            layout("expression_noise_2d_array_layout", noise_texture_2d_array()),
+        )
+    });
+
+    // Expressions that went unused have had their shaders removed.
+    let removed = |key: &ComputeNoisePipelineKey| {
+        key.type_id == TypeId::of::<ExpressionNoise>() && !expression_shaders.shaders.contains_key(&key.variant)
+    };
+    pipeline.shaders.retain(|key, _| !removed(key));
+    pipeline.shader_defs.retain(|key, _| !removed(key));
+    pipeline.layouts.retain(|key, _| !removed(key));
+
+    for (variant, (shader, _)) in &expression_shaders.shaders {
+        for (dimension, shader_defs, layout) in [
+            (TextureViewDimension::D2, &["2D"][..], &*layout_2d),
+            (TextureViewDimension::D2Array, &["2D", "ARRAY"], &*layout_array),
+            (TextureViewDimension::D3, &["3D"], &*layout_3d),
+            (TextureViewDimension::Cube, &["CUBE", "ARRAY"], &*layout_array),
+        ] {
+            let key = ComputeNoisePipelineKey {
+                type_id: TypeId::of::<ExpressionNoise>(),
+                dimension,
+                op: NoiseOp::Generator,
+                variant: *variant,
+            };
+            if pipeline.has_shader(key) { continue; }
+            pipeline.register_generator(key.type_id, key.variant, shader, &[(dimension, shader_defs)], Some(layout));
+        }
+    }
 }
 
 pub fn load_modifier_shader<T: ComputeNoiseModifier>(world: &mut World) {
-    let shader = match T::shader() {
-        ShaderRef::Default => None,
-        ShaderRef::Handle(handle) => Some(handle),
-        ShaderRef::Path(path) => Some(world.resource::<AssetServer>().load(path)),
-    }.unwrap();
- 
-    let mut pipeline = world.resource_mut::<ComputeNoisePipeline>();
-     pipeline.shaders.insert(
-        ComputeNoisePipelineKey {
-            type_id: TypeId::of::<T>(),
-            dimension: TextureDimension::D2,
-            op: NoiseOp::Modifier,
-        },
-        shader.clone(),
-    );
+    let Some(shader) = load_shader::<T>(world, T::shader()) else { return };
 
-    pipeline.shaders.insert(
-        ComputeNoisePipelineKey {
-            type_id: TypeId::of::<T>(),
-            dimension: TextureDimension::D3,
-            op: NoiseOp::Modifier,
-        },
-        shader,
-    );
+    let dimensions: &[_] = match T::SUPPORTS_3D {
+        true => &[TextureViewDimension::D2, TextureViewDimension::D2Array, TextureViewDimension::D3],
+        false => &[TextureViewDimension::D2, TextureViewDimension::D2Array],
+    };
 
-    pipeline.shader_defs.insert(
-        ComputeNoisePipelineKey {
-            type_id: TypeId::of::<T>(),
-            dimension: TextureDimension::D2,
-            op: NoiseOp::Modifier,
-        },
-        vec!["2D".into()]
-    );
-    pipeline.shader_defs.insert(
-        ComputeNoisePipelineKey {
+    let buffer_layout = T::buffer_layout();
+    for &dimension in dimensions {
+        let (shader_defs, texture): (&[&str], _) = match dimension {
+            TextureViewDimension::D3 => (&["3D"], noise_texture_3d()),
+            TextureViewDimension::D2Array => (&["2D", "ARRAY"], noise_texture_2d_array()),
+            _ => (&["2D"], noise_texture_2d()),
+        };
+        let key = ComputeNoisePipelineKey {
             type_id: TypeId::of::<T>(),
-            dimension: TextureDimension::D3,
+            dimension,
             op: NoiseOp::Modifier,
-        },
-        vec!["3D".into()]
-    );
+            variant: 0,
+        };
+
+        if !buffer_layout.is_empty() {
+            let entries: Vec<BindGroupLayoutEntry> = [texture, texture].into_iter()
+                .chain(buffer_layout.iter().copied())
+                .enumerate()
+                .map(|(i, entry)| entry.build(i as u32, ShaderStages::COMPUTE))
+                .collect();
+            let layout = world.resource::<RenderDevice>().create_bind_group_layout("modifier_layout", &entries);
+            world.resource_mut::<ComputeNoisePipeline>().layouts.insert(key, layout);
+        }
+
+        let mut pipeline = world.resource_mut::<ComputeNoisePipeline>();
+        pipeline.shaders.insert(key, shader.clone());
+        pipeline.shader_defs.insert(key, shader_defs.iter().map(|&shader_def| shader_def.into()).collect());
+    }
+}
+
+/// Pipelines requested with [`ComputeNoiseQueue::warm_up`](crate::noise_queue::ComputeNoiseQueue::warm_up) that haven't compiled yet.
+#[derive(Resource, Default)]
+pub struct ComputeNoiseWarmUp {
+    pub(crate) pending: Vec<(ComputeNoisePipelineKey, &'static str)>,
+    compiling: Vec<(CachedComputePipelineId, &'static str, TextureViewDimension)>,
+    /// Pipelines that failed since the last extract, passed back to the main world.
+    pub(crate) failed: Vec<(&'static str, TextureViewDimension)>,
+}
+
+impl ComputeNoiseWarmUp {
+    pub fn is_ready(&self) -> bool {
+        self.pending.is_empty() && self.compiling.is_empty()
+    }
+}
+
+pub fn warm_up_pipelines(
+    mut warm_up: ResMut<ComputeNoiseWarmUp>,
+    pipeline: Res<ComputeNoisePipeline>,
+    mut pipelines: ResMut<SpecializedComputePipelines<ComputeNoisePipeline>>,
+    mut pipeline_cache: ResMut<PipelineCache>,
+) {
+    if warm_up.is_ready() { return; }
+
+    for (key, type_name) in std::mem::take(&mut warm_up.pending) {
+        match pipeline.queue_pipeline(&mut pipelines, &pipeline_cache, key, type_name) {
+            Ok(id) => warm_up.compiling.push((id, type_name, key.dimension)),
+            Err(error) => {
+                error!("{error} for {:?} - did not warm up pipeline.", key.dimension);
+                warm_up.failed.push((type_name, key.dimension));
+            }
+        }
+    }
+
+    // Newly specialized pipelines aren't in the cache until it's processed.
+    pipeline_cache.process_queue();
+
+    // The pipeline cache has already logged why a pipeline failed to compile.
+    let ComputeNoiseWarmUp { compiling, failed, .. } = &mut *warm_up;
+    compiling.retain(|&(id, type_name, dimension)| {
+        if pipeline_compile_error(&pipeline_cache, id).is_some() {
+            failed.push((type_name, dimension));
+            return false;
+        }
+        !matches!(pipeline_cache.get_compute_pipeline_state(id), CachedPipelineState::Ok(_))
+    });
+}
+
+/// Why a pipeline failed to compile. Shaders and imports that haven't loaded yet aren't errors,
+/// the pipeline cache tries again once they have.
+pub(crate) fn pipeline_compile_error(pipeline_cache: &PipelineCache, id: CachedComputePipelineId) -> Option<&PipelineCacheError> {
+    match pipeline_cache.get_compute_pipeline_state(id) {
+        CachedPipelineState::Err(error @ (PipelineCacheError::ProcessShaderError(_) | PipelineCacheError::CreateShaderModule(_))) => Some(error),
+        _ => None,
+    }
 }
 
 #[derive(Resource)]
 pub struct ComputeNoisePipeline {
     pub generator_layout_2d: BindGroupLayout,
     pub generator_layout_3d: BindGroupLayout,
+    /// Used by array and cube textures, which bind all their layers at once.
+    pub generator_layout_2d_array: BindGroupLayout,
     pub modifier_layout_2d: BindGroupLayout,
     pub modifier_layout_3d: BindGroupLayout,
+    pub modifier_layout_2d_array: BindGroupLayout,
+    /// Bound at group 1 of every pipeline, see [`ComputeNoiseDispatch`].
+    pub dispatch_layout: BindGroupLayout,
+    layouts: HashMap<ComputeNoisePipelineKey, BindGroupLayout>,
     shaders: HashMap<ComputeNoisePipelineKey, Handle<Shader>>,
     shader_defs: HashMap<ComputeNoisePipelineKey, Vec<ShaderDefVal>>,
-    _util_shader: Handle<Shader>,
+    /// Modules imported by the noise shaders, kept loaded so their import paths resolve.
+    _shader_modules: Vec<Handle<Shader>>,
 }
 
 impl FromWorld for ComputeNoisePipeline {
@@ -157,6 +304,16 @@ impl FromWorld for ComputeNoisePipeline {
                 )
             )
         );
+        let generator_layout_2d_array = render_device.create_bind_group_layout(
+            "noise_2d_array_layout",
+            &BindGroupLayoutEntries::sequential(
+                ShaderStages::COMPUTE,
+                (
+                    noise_texture_2d_array(),
+                    uniform_buffer_sized(false, None),
+                )
+            )
+        );
         let modifier_layout_2d = render_device.create_bind_group_layout(
             "noise_2d_layout",
             &BindGroupLayoutEntries::sequential(
@@ -177,26 +334,101 @@ impl FromWorld for ComputeNoisePipeline {
                 )
             )
         );
+        let modifier_layout_2d_array = render_device.create_bind_group_layout(
+            "noise_2d_array_layout",
+            &BindGroupLayoutEntries::sequential(
+                ShaderStages::COMPUTE,
+                (
+                    noise_texture_2d_array(),
+                    noise_texture_2d_array(),
+                )
+            )
+        );
+
+        let dispatch_layout = render_device.create_bind_group_layout(
+            "noise_dispatch_layout",
+            &BindGroupLayoutEntries::single(
+                ShaderStages::COMPUTE,
+                uniform_buffer_sized(true, NonZeroU64::new(std::mem::size_of::<ComputeNoiseDispatch>() as u64)),
+            )
+        );
 
         Self {
             generator_layout_2d,
             generator_layout_3d,
+            generator_layout_2d_array,
             modifier_layout_2d,
             modifier_layout_3d,
+            modifier_layout_2d_array,
+            dispatch_layout,
+            layouts: HashMap::new(),
             shaders: HashMap::new(),
             shader_defs: HashMap::new(),
-            _util_shader: world.resource::<AssetServer>().load("embedded://bevy_compute_noise/noise/shaders/util.wgsl"),
+            _shader_modules: [
+                "noise/shaders/util.wgsl",
+                "noise/shaders/dispatch.wgsl",
+                "noise/shaders/math.wgsl",
+                "noise/shaders/fbm.wgsl",
+                "noise/generators/shaders/perlin.wgsl",
+                "noise/generators/shaders/worley.wgsl",
+                "noise/shaders/triplanar.wgsl",
+            ].map(|path| world.resource::<AssetServer>().load(format!("embedded://bevy_compute_noise/{path}"))).into(),
         }
     }
 }
 
 impl ComputeNoisePipeline {
+    pub fn has_shader(&self, key: ComputeNoisePipelineKey) -> bool {
+        self.shaders.contains_key(&key)
+    }
+
+    /// Uses `shader` for the generator `type_id` on each dimension, with the shader defs given for it.
+    /// Generators that bind more than their texture and uniform pass their own `layout`.
+    pub(crate) fn register_generator(
+        &mut self,
+        type_id: TypeId,
+        variant: u64,
+        shader: &Handle<Shader>,
+        dimensions: &[(TextureViewDimension, &[&'static str])],
+        layout: Option<&BindGroupLayout>,
+    ) {
+        for &(dimension, shader_defs) in dimensions {
+            let key = ComputeNoisePipelineKey { type_id, dimension, op: NoiseOp::Generator, variant };
+            self.shaders.insert(key, shader.clone());
+            self.shader_defs.insert(key, shader_defs.iter().map(|&shader_def| shader_def.into()).collect());
+            if let Some(layout) = layout {
+                self.layouts.insert(key, layout.clone());
+            }
+        }
+    }
+
+    /// Specializes the pipeline for `key`, or returns [`ComputeNoiseError::MissingShader`] if no shader was registered for it.
+    /// Pipelines are only specialized through here, so [`specialize`](SpecializedComputePipeline::specialize) always finds a shader.
+    pub(crate) fn queue_pipeline(
+        &self,
+        pipelines: &mut SpecializedComputePipelines<Self>,
+        pipeline_cache: &PipelineCache,
+        key: ComputeNoisePipelineKey,
+        type_name: &'static str,
+    ) -> Result<CachedComputePipelineId, ComputeNoiseError> {
+        if !self.has_shader(key) {
+            return Err(ComputeNoiseError::MissingShader(type_name));
+        }
+        Ok(pipelines.specialize(pipeline_cache, self, key))
+    }
+
     pub fn get_layout(&self, key: ComputeNoisePipelineKey) -> &BindGroupLayout {
+        if let Some(layout) = self.layouts.get(&key) {
+            return layout;
+        }
+
         match (key.dimension, key.op) {
-            (TextureDimension::D2, NoiseOp::Generator) => &self.generator_layout_2d,
-            (TextureDimension::D3, NoiseOp::Generator) => &self.generator_layout_3d, 
-            (TextureDimension::D2, NoiseOp::Modifier) => &self.modifier_layout_2d,
-            (TextureDimension::D3, NoiseOp::Modifier) => &self.modifier_layout_3d, 
+            (TextureViewDimension::D3, NoiseOp::Generator) => &self.generator_layout_3d,
+            (TextureViewDimension::D2Array | TextureViewDimension::Cube, NoiseOp::Generator) => &self.generator_layout_2d_array,
+            (_, NoiseOp::Generator) => &self.generator_layout_2d,
+            (TextureViewDimension::D3, NoiseOp::Modifier) => &self.modifier_layout_3d,
+            (TextureViewDimension::D2Array, NoiseOp::Modifier) => &self.modifier_layout_2d_array,
+            (_, NoiseOp::Modifier) => &self.modifier_layout_2d,
             _ => unreachable!(),
         }
     }
@@ -205,8 +437,22 @@ impl ComputeNoisePipeline {
 #[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
 pub struct ComputeNoisePipelineKey {
     pub type_id: TypeId,
-    pub dimension: TextureDimension,
+    /// Dimension of the storage texture the pipeline writes to, see [`pipeline_dimension`].
+    pub dimension: TextureViewDimension,
     pub op: NoiseOp,
+    /// Tells apart pipelines of the same type, see [`ComputeNoise::pipeline_variant`](crate::noise::ComputeNoise::pipeline_variant).
+    pub variant: u64,
+}
+
+/// Storage textures can't be cubes, so cubes are bound as 2D arrays of their faces. Only generators
+/// get their own cube pipeline, which samples the noise in 3D so the faces line up.
+pub fn pipeline_dimension(dimension: TextureViewDimension, op: NoiseOp) -> TextureViewDimension {
+    match (dimension, op) {
+        (TextureViewDimension::D3, _) => TextureViewDimension::D3,
+        (TextureViewDimension::Cube, NoiseOp::Generator) => TextureViewDimension::Cube,
+        (TextureViewDimension::D2Array | TextureViewDimension::Cube, _) => TextureViewDimension::D2Array,
+        _ => TextureViewDimension::D2,
+    }
 }
 
 impl SpecializedComputePipeline for ComputeNoisePipeline {
@@ -217,9 +463,9 @@ impl SpecializedComputePipeline for ComputeNoisePipeline {
 
         ComputePipelineDescriptor {
             label: Some("compute_noise_pipeline".into()),
-            layout: vec![layout.clone()],
+            layout: vec![layout.clone(), self.dispatch_layout.clone()],
             push_constant_ranges: Vec::new(),
-            shader: self.shaders.get(&key).unwrap().clone(),
+            shader: self.shaders.get(&key).expect("pipelines are specialized through queue_pipeline, which checks for a shader").clone(),
             shader_defs: self.shader_defs.get(&key).cloned().unwrap_or_default(),
             entry_point: "main".into(),
             zero_initialize_workgroup_memory: false,
@@ -239,6 +485,11 @@ pub fn noise_texture_3d() -> BindGroupLayoutEntryBuilder {
     }.into_bind_group_layout_entry_builder()
 }
 
+/// Every layer of an array or cube texture, layers are dispatched one at a time.
+pub fn noise_texture_2d_array() -> BindGroupLayoutEntryBuilder {
+    texture_storage_2d_array(TextureFormat::Rgba8Unorm, StorageTextureAccess::ReadWrite)
+}
+
 
 #[derive(Copy, Clone, Debug, Hash, Eq, PartialEq)]
 pub enum NoiseOp {
@@ -2,16 +2,34 @@ use bevy::{
     prelude::*,
     render::{
         render_asset::RenderAssets,
-        render_resource::{BindGroupEntry, IntoBinding, PipelineCache, SpecializedComputePipelines},
+        render_resource::{BindGroup, BindGroupEntry, BufferId, IntoBinding, PipelineCache, SpecializedComputePipelines, TextureViewDescriptor, TextureViewDimension, TextureViewId},
         renderer::RenderDevice,
         texture::GpuImage,
     },
+    utils::HashMap,
 };
 
 use crate::{
-    noise_queue::{RenderComputeNoise, ComputeNoiseBufferQueue, ComputeNoiseRenderQueue}, render::pipeline::ComputeNoisePipeline
+    buffer_pool::MAX_UNUSED_FRAMES,
+    noise_queue::{RenderComputeNoise, ComputeNoiseBufferQueue, ComputeNoiseRenderQueue}, render::pipeline::{ComputeNoisePipeline, ComputeNoisePipelineKey}
 };
 
+#[derive(PartialEq, Eq, Hash)]
+struct BindGroupKey {
+    key: ComputeNoisePipelineKey,
+    texture_views: Vec<TextureViewId>,
+    buffers: Vec<BufferId>,
+    mip_level: u32,
+}
+
+/// Bind groups are reused for as long as the same texture views and buffers are bound,
+/// which with pooled buffers is the case for noise queued into the same image every frame.
+#[derive(Resource, Default)]
+pub(crate) struct ComputeNoiseBindGroupCache {
+    bind_groups: HashMap<BindGroupKey, (BindGroup, u32)>,
+}
+
+#[allow(clippy::too_many_arguments)]
 pub fn prepare_render_noise(
     pipeline: Res<ComputeNoisePipeline>,
     mut pipelines: ResMut<SpecializedComputePipelines<ComputeNoisePipeline>>,
@@ -20,24 +38,71 @@ pub fn prepare_render_noise(
     render_device: Res<RenderDevice>,
     queue: Res<ComputeNoiseBufferQueue>,
     mut render_queue: ResMut<ComputeNoiseRenderQueue>,
+    mut bind_group_cache: ResMut<ComputeNoiseBindGroupCache>,
 ) {
-    for sequence in queue.queue.iter() {
+    bind_group_cache.bind_groups.retain(|_, (_, unused_frames)| {
+        *unused_frames += 1;
+        *unused_frames <= MAX_UNUSED_FRAMES
+    });
+
+    let pending = std::mem::take(&mut render_queue.pending);
+    for sequence in queue.queue.iter().cloned().chain(pending) {
+        let ready = sequence.iter()
+            .flat_map(|noise| noise.images.iter())
+            .all(|handle| gpu_images.get(handle).is_some());
+        if !ready {
+            render_queue.pending.push(sequence);
+            continue;
+        }
+
+        let pipeline_ids = sequence.iter()
+            .map(|noise| pipeline.queue_pipeline(&mut pipelines, &pipeline_cache, noise.key, noise.type_name).map_err(|error| (error, noise.key.dimension)))
+            .collect::<Result<Vec<_>, _>>();
+        let pipeline_ids = match pipeline_ids {
+            Ok(pipeline_ids) => pipeline_ids,
+            Err((error, dimension)) => {
+                error!("{error} for {dimension:?} - did not queue compute noise.");
+                continue;
+            }
+        };
+
         let mut render_sequence = Vec::new();
-        
-        for noise in sequence {
-            let images: Option<Vec<_>> = noise.images.iter()
-                .map(|handle| gpu_images.get(handle))
+        for (noise, pipeline_id) in sequence.iter().zip(pipeline_ids) {
+            let images: Vec<_> = noise.images.iter()
+                .filter_map(|handle| gpu_images.get(handle))
                 .collect();
-            
-            if let Some(images) = images {
+
+            let cache_key = BindGroupKey {
+                key: noise.key,
+                texture_views: images.iter().map(|image| image.texture_view.id()).collect(),
+                buffers: noise.buffers.iter().map(|buffer| buffer.id()).collect(),
+                mip_level: noise.mip_level,
+            };
+            let (bind_group, unused_frames) = bind_group_cache.bind_groups.entry(cache_key).or_insert_with(|| {
+                // Storage textures can only bind a single mip, the output is written to `mip_level`
+                // and inputs are read from the mip before it. Cubes are bound as an array of their faces.
+                let texture_views: Vec<_> = images.iter().enumerate()
+                    .map(|(i, image)| {
+                        if !noise.size.is_layered() && image.mip_level_count == 1 { return image.texture_view.clone(); }
+                        let mip_level = if i + 1 == images.len() { noise.mip_level } else { noise.mip_level.saturating_sub(1) };
+                        image.texture.create_view(&TextureViewDescriptor {
+                            label: Some("compute_noise_view"),
+                            dimension: Some(if noise.size.is_layered() { TextureViewDimension::D2Array } else { noise.size.into() }),
+                            base_mip_level: mip_level,
+                            mip_level_count: Some(1),
+                            ..default()
+                        })
+                    })
+                    .collect();
+
                 let layout = pipeline.get_layout(noise.key);
                 let bind_group = render_device.create_bind_group(
                     Some("image_bind_group".into()),
                     &layout,
-                    images.iter().enumerate()
-                        .map(|(i, image)| BindGroupEntry {
+                    texture_views.iter().enumerate()
+                        .map(|(i, texture_view)| BindGroupEntry {
                             binding: i as u32,
-                            resource: image.texture_view.into_binding(),
+                            resource: texture_view.into_binding(),
                         })
                         .chain(
                             noise.buffers.iter().enumerate()
@@ -49,24 +114,26 @@ pub fn prepare_render_noise(
                         .collect::<Vec<_>>()
                         .as_slice(),
                 );
+                (bind_group, 0)
+            });
+            *unused_frames = 0;
 
-                let pipeline_id = pipelines.specialize(
-                    &pipeline_cache, 
-                    &pipeline, 
-                    noise.key,
-                );
-
-                render_sequence.push(RenderComputeNoise {
-                    key: noise.key,
-                    bind_group,
-                    pipeline_id,
-                    size: noise.size,
-                });
-            }
+            render_sequence.push(RenderComputeNoise {
+                key: noise.key,
+                bind_group: bind_group.clone(),
+                pipeline_id,
+                size: noise.size,
+                frame_break: noise.frame_break,
+                priority: noise.priority,
+                region: noise.region,
+                output: noise.images.last().unwrap().id(),
+                next_z: 0,
+                leases: noise.leases.clone(),
+            });
         }
 
         if !render_sequence.is_empty() {
             render_queue.queue.push(render_sequence);
         }
     }
-}
\ No newline at end of file
+}
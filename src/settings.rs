@@ -0,0 +1,52 @@
+use bevy::{prelude::*, render::extract_resource::ExtractResource};
+
+/// Limits how much noise is generated each frame.
+///
+/// Sequences are dispatched in order of priority, highest first, until a budget runs out and the
+/// rest carry over to the next frame. At least one dispatch always runs so nothing gets stuck behind
+/// a budget smaller than a single dispatch. Use tight budgets during gameplay and loosen them during
+/// loading screens.
+#[derive(Resource, ExtractResource, Clone, Copy, Default, Debug)]
+pub struct ComputeNoiseSettings {
+    /// Maximum number of texels written per frame.
+    pub texel_budget: Option<u64>,
+    /// Maximum number of dispatches per frame.
+    pub dispatch_budget: Option<u32>,
+    /// 3D textures are dispatched in slabs of at most this many z slices, so a budget can split them across frames.
+    pub slab_depth: Option<u32>,
+    pub mode: ComputeNoiseDispatchMode,
+}
+
+/// How the noise dispatches are submitted to the GPU.
+#[derive(Clone, Copy, Default, PartialEq, Eq, Debug)]
+pub enum ComputeNoiseDispatchMode {
+    /// Record into a separate command encoder that's submitted before the render graph runs.
+    #[default]
+    Encoder,
+    /// Record in [`ComputeNoiseNode`](crate::ComputeNoiseNode), so the dispatches can be ordered against
+    /// other nodes with edges to [`ComputeNoiseLabel`](crate::ComputeNoiseLabel).
+    RenderGraph,
+}
+
+impl ComputeNoiseSettings {
+    /// End of the slab starting at z slice `start` of a texture `depth` slices deep.
+    pub(crate) fn slab_end(&self, start: u32, depth: u32) -> u32 {
+        let slab_depth = self.slab_depth.unwrap_or(depth).max(1);
+        start.saturating_add(slab_depth).min(depth)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn slabs_cover_depth() {
+        let settings = ComputeNoiseSettings { slab_depth: Some(16), ..default() };
+        assert_eq!(settings.slab_end(0, 40), 16);
+        assert_eq!(settings.slab_end(32, 40), 40);
+        assert_eq!(settings.slab_end(0, 1), 1);
+        assert_eq!(ComputeNoiseSettings::default().slab_end(0, 64), 64);
+        assert_eq!(ComputeNoiseSettings { slab_depth: Some(0), ..default() }.slab_end(3, 64), 4);
+    }
+}
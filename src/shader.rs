@@ -8,6 +8,14 @@ pub trait ComputeNoiseShader {
     fn import_path() -> &'static str;
     fn struct_name() -> Option<&'static str>;
     fn function_name() -> &'static str;
+    /// Module of the function sampled on 3D and cube textures, when it differs from [`Self::import_path`].
+    fn import_path_3d() -> &'static str {
+        Self::import_path()
+    }
+    /// Function sampled on 3D and cube textures, when it differs from [`Self::function_name`].
+    fn function_name_3d() -> &'static str {
+        Self::function_name()
+    }
 }
 
 pub(crate) fn generate_noise_shader(sequence: &ComputeNoiseSequence) -> ShaderSource {
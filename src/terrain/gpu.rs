@@ -0,0 +1,191 @@
+use bevy::{
+    prelude::*,
+    render::{
+        mesh::allocator::MeshAllocator,
+        render_asset::RenderAssets,
+        render_resource::{binding_types::{storage_buffer_read_only_sized, storage_buffer_sized, texture_2d, uniform_buffer_sized}, BindGroup, BindGroupEntries, BindGroupLayout, BindGroupLayoutEntries, Buffer, BufferInitDescriptor, BufferDescriptor, BufferUsages, CachedComputePipelineId, ComputePipelineDescriptor, PipelineCache, ShaderStages, TextureSampleType},
+        renderer::RenderDevice,
+        texture::GpuImage,
+        MainWorld,
+    },
+};
+use bytemuck::{Pod, Zeroable};
+
+use crate::{noise_queue::{ComputeNoiseQueue, ComputeNoiseRenderQueue}, render::compute::ComputeNoiseDispatches};
+
+use super::{NoiseTerrain, TerrainVertex};
+
+/// Bytes per vertex of the meshes [`NoiseTerrain`] builds, a position, normal and uv.
+const VERTEX_SIZE: u64 = 8 * 4;
+const WORKGROUP_SIZE: u32 = 64;
+
+#[derive(Clone, Copy, Pod, Zeroable)]
+#[repr(C)]
+struct TerrainUniform {
+    size: Vec2,
+    uv_rect_size: Vec2,
+    height_scale: f32,
+    vertex_count: u32,
+    _padding: [u32; 2],
+}
+
+pub(crate) struct TerrainDisplacement {
+    mesh: AssetId<Mesh>,
+    heightmap: Handle<Image>,
+    uniform: TerrainUniform,
+    vertices: Vec<TerrainVertex>,
+}
+
+// Main World
+/// Flat terrain meshes waiting to be displaced on the GPU.
+#[derive(Resource, Default)]
+pub(crate) struct NoiseTerrainDisplacements(Vec<TerrainDisplacement>);
+
+impl NoiseTerrainDisplacements {
+    pub fn push(&mut self, terrain: &NoiseTerrain, mesh: AssetId<Mesh>) {
+        let vertices = terrain.vertices();
+        self.0.push(TerrainDisplacement {
+            mesh,
+            heightmap: terrain.heightmap.clone(),
+            uniform: TerrainUniform {
+                size: terrain.size,
+                uv_rect_size: terrain.uv_rect.size(),
+                height_scale: terrain.height_scale,
+                vertex_count: vertices.len() as u32,
+                _padding: [0; 2],
+            },
+            vertices,
+        });
+    }
+}
+
+// Render World
+/// Displacements waiting on their heightmap to be written and their mesh to be uploaded.
+#[derive(Resource, Default)]
+pub(crate) struct PendingTerrainDisplacements(Vec<TerrainDisplacement>);
+
+/// Recorded after the frame's noise dispatches, then copied over the mesh's vertices.
+pub(crate) struct PreparedDisplacement {
+    pub pipeline_id: CachedComputePipelineId,
+    pub bind_group: BindGroup,
+    pub workgroups: u32,
+    pub output: Buffer,
+    pub vertex_buffer: Buffer,
+    /// Byte offset of the mesh in `vertex_buffer`.
+    pub vertex_offset: u64,
+}
+
+#[derive(Resource)]
+pub(crate) struct TerrainDisplacementPipeline {
+    layout: BindGroupLayout,
+    pipeline_id: CachedComputePipelineId,
+}
+
+impl FromWorld for TerrainDisplacementPipeline {
+    fn from_world(world: &mut World) -> Self {
+        let layout = world.resource::<RenderDevice>().create_bind_group_layout(
+            "terrain_displacement_layout",
+            &BindGroupLayoutEntries::sequential(
+                ShaderStages::COMPUTE,
+                (
+                    texture_2d(TextureSampleType::Float { filterable: true }),
+                    uniform_buffer_sized(false, None),
+                    storage_buffer_read_only_sized(false, None),
+                    storage_buffer_sized(false, None),
+                )
+            )
+        );
+        let shader = world.resource::<AssetServer>().load("embedded://bevy_compute_noise/terrain/shaders/displace_terrain.wgsl");
+
+        let pipeline_id = world.resource::<PipelineCache>().queue_compute_pipeline(ComputePipelineDescriptor {
+            label: Some("terrain_displacement_pipeline".into()),
+            layout: vec![layout.clone()],
+            push_constant_ranges: Vec::new(),
+            shader,
+            shader_defs: Vec::new(),
+            entry_point: "main".into(),
+            zero_initialize_workgroup_memory: false,
+        });
+
+        Self { layout, pipeline_id }
+    }
+}
+
+pub(crate) fn extract_terrain_displacements(
+    mut world: ResMut<MainWorld>,
+    mut pending: ResMut<PendingTerrainDisplacements>,
+) {
+    world.resource_scope(|world, mut displacements: Mut<NoiseTerrainDisplacements>| {
+        // Meshes replaced before they were displaced have been removed.
+        let meshes = world.resource::<Assets<Mesh>>();
+        pending.0.retain(|displacement| meshes.contains(displacement.mesh));
+
+        let noise_queue = world.resource::<ComputeNoiseQueue>();
+        // Noise waiting on its images to load hasn't reached the render world yet.
+        let (waiting, ready) = displacements.0.drain(..).partition(|displacement| noise_queue.is_writing(&displacement.heightmap));
+        displacements.0 = waiting;
+        pending.0.extend::<Vec<_>>(ready);
+    });
+}
+
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn prepare_terrain_displacements(
+    mut pending: ResMut<PendingTerrainDisplacements>,
+    mut dispatches: ResMut<ComputeNoiseDispatches>,
+    pipeline: Res<TerrainDisplacementPipeline>,
+    mut pipeline_cache: ResMut<PipelineCache>,
+    render_queue: Res<ComputeNoiseRenderQueue>,
+    gpu_images: Res<RenderAssets<GpuImage>>,
+    mesh_allocator: Res<MeshAllocator>,
+    render_device: Res<RenderDevice>,
+) {
+    if pending.0.is_empty() { return; }
+
+    // The pipeline is queued when the plugin finishes, so it isn't in the cache until it's processed.
+    pipeline_cache.process_queue();
+    if pipeline_cache.get_compute_pipeline(pipeline.pipeline_id).is_none() { return; }
+
+    for displacement in std::mem::take(&mut pending.0) {
+        // Recorded after this frame's dispatches, so only noise left for later frames has to be waited on.
+        let heightmap = gpu_images.get(&displacement.heightmap).filter(|_| !render_queue.is_writing(displacement.heightmap.id()));
+        let (Some(heightmap), Some(vertex_slice)) = (heightmap, mesh_allocator.mesh_vertex_slice(&displacement.mesh)) else {
+            pending.0.push(displacement);
+            continue;
+        };
+
+        let uniform = render_device.create_buffer_with_data(&BufferInitDescriptor {
+            label: Some("terrain_displacement_uniform"),
+            contents: bytemuck::bytes_of(&displacement.uniform),
+            usage: BufferUsages::UNIFORM,
+        });
+        let vertices = render_device.create_buffer_with_data(&BufferInitDescriptor {
+            label: Some("terrain_displacement_vertices"),
+            contents: bytemuck::cast_slice(&displacement.vertices),
+            usage: BufferUsages::STORAGE,
+        });
+        let output = render_device.create_buffer(&BufferDescriptor {
+            label: Some("terrain_displacement_output"),
+            size: displacement.vertices.len() as u64 * VERTEX_SIZE,
+            usage: BufferUsages::STORAGE | BufferUsages::COPY_SRC,
+            mapped_at_creation: false,
+        });
+
+        dispatches.terrain.push(PreparedDisplacement {
+            pipeline_id: pipeline.pipeline_id,
+            bind_group: render_device.create_bind_group(
+                "terrain_displacement_bind_group",
+                &pipeline.layout,
+                &BindGroupEntries::sequential((
+                    &heightmap.texture_view,
+                    uniform.as_entire_binding(),
+                    vertices.as_entire_binding(),
+                    output.as_entire_binding(),
+                )),
+            ),
+            workgroups: displacement.uniform.vertex_count.div_ceil(WORKGROUP_SIZE),
+            output,
+            vertex_buffer: vertex_slice.buffer.clone(),
+            vertex_offset: vertex_slice.range.start as u64 * VERTEX_SIZE,
+        });
+    }
+}
@@ -0,0 +1,322 @@
+use std::sync::{Arc, Mutex};
+
+use bevy::{prelude::*, render::{mesh::{Indices, PrimitiveTopology}, primitives::Aabb, render_asset::RenderAssetUsages}};
+use bytemuck::{Pod, Zeroable};
+
+use crate::readback::ComputeNoiseReadbackQueue;
+
+pub(crate) mod gpu;
+
+/// A subdivided plane in XZ, centred on the origin, displaced by the red channel of a 2D heightmap.
+///
+/// As a component, the mesh is built once the noise queued into `heightmap` has been written and inserted
+/// as a [`Mesh3d`], then built again whenever the component changes. The heightmap is read back from the GPU,
+/// unless `gpu_displacement` is set, in which case a flat mesh is displaced by a compute shader straight
+/// into its vertex buffer and the heights never reach the CPU.
+///
+/// [`mesh`](Self::mesh) builds the mesh from a [`NoiseHeightmap`] instead, e.g. one baked to disk and loaded back.
+#[derive(Component, Clone, Reflect, Debug, PartialEq)]
+#[reflect(Component, Default)]
+pub struct NoiseTerrain {
+    pub heightmap: Handle<Image>,
+    /// Size of the plane in world units.
+    pub size: Vec2,
+    /// Quads along each side at LOD 0.
+    pub subdivisions: UVec2,
+    /// Each level halves the subdivisions, down to a single quad.
+    pub lod: u32,
+    /// Height of a texel with a value of 1.0.
+    pub height_scale: f32,
+    /// How far the skirt around the border hangs below it, hiding cracks between chunks at
+    /// different LODs. 0 leaves the skirt out.
+    pub skirt_depth: f32,
+    /// Part of the heightmap the plane covers, so chunks can share one heightmap. Also used as the mesh's uvs.
+    pub uv_rect: Rect,
+    pub gpu_displacement: bool,
+}
+
+impl Default for NoiseTerrain {
+    fn default() -> Self {
+        Self {
+            heightmap: Handle::default(),
+            size: Vec2::splat(64.0),
+            subdivisions: UVec2::splat(64),
+            lod: 0,
+            height_scale: 8.0,
+            skirt_depth: 0.0,
+            uv_rect: Rect::new(0.0, 0.0, 1.0, 1.0),
+            gpu_displacement: false,
+        }
+    }
+}
+
+/// Where a vertex is on the plane, matches `TerrainVertex` in `displace_terrain.wgsl`.
+#[derive(Clone, Copy, Pod, Zeroable, Debug, PartialEq)]
+#[repr(C)]
+pub(crate) struct TerrainVertex {
+    /// Position in XZ.
+    position: Vec2,
+    uv: Vec2,
+    /// Distance below the surface, for skirts.
+    drop: f32,
+    _padding: f32,
+}
+
+impl NoiseTerrain {
+    /// Quads along each side at the current LOD.
+    pub fn lod_subdivisions(&self) -> UVec2 {
+        (self.subdivisions >> self.lod.min(31)).max(UVec2::ONE)
+    }
+
+    /// Bounds of the mesh, from the lowest possible height to the highest.
+    pub fn aabb(&self) -> Aabb {
+        let half_size = self.size / 2.0;
+        Aabb::from_min_max(
+            Vec3::new(-half_size.x, -self.skirt_depth, -half_size.y),
+            Vec3::new(half_size.x, self.height_scale, half_size.y),
+        )
+    }
+
+    /// Builds the mesh on the CPU, sampling heights and normals from `heightmap`.
+    pub fn mesh(&self, heightmap: &NoiseHeightmap) -> Mesh {
+        let world_per_texel = self.world_per_texel(heightmap.size());
+        let (positions, normals) = self.vertices().iter()
+            .map(|vertex| {
+                let height = heightmap.sample(vertex.uv) * self.height_scale - vertex.drop;
+                let slope = heightmap.gradient(vertex.uv) * self.height_scale / world_per_texel;
+                (
+                    [vertex.position.x, height, vertex.position.y],
+                    Vec3::new(-slope.x, 1.0, -slope.y).normalize().to_array(),
+                )
+            })
+            .unzip();
+        self.build_mesh(positions, normals)
+    }
+
+    /// Flat mesh for the GPU to displace, it has the same layout as the one [`mesh`](Self::mesh) builds.
+    pub(crate) fn flat_mesh(&self) -> Mesh {
+        let (positions, normals) = self.vertices().iter()
+            .map(|vertex| ([vertex.position.x, -vertex.drop, vertex.position.y], [0.0, 1.0, 0.0]))
+            .unzip();
+        self.build_mesh(positions, normals)
+    }
+
+    fn build_mesh(&self, positions: Vec<[f32; 3]>, normals: Vec<[f32; 3]>) -> Mesh {
+        let uvs: Vec<[f32; 2]> = self.vertices().iter().map(|vertex| vertex.uv.to_array()).collect();
+        Mesh::new(PrimitiveTopology::TriangleList, RenderAssetUsages::default())
+            .with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, positions)
+            .with_inserted_attribute(Mesh::ATTRIBUTE_NORMAL, normals)
+            .with_inserted_attribute(Mesh::ATTRIBUTE_UV_0, uvs)
+            .with_inserted_indices(Indices::U32(self.indices()))
+    }
+
+    /// World units between texels of a heightmap of `size`.
+    fn world_per_texel(&self, size: UVec2) -> Vec2 {
+        self.size / (self.uv_rect.size() * (size.saturating_sub(UVec2::ONE)).max(UVec2::ONE).as_vec2())
+    }
+
+    /// The grid row by row, then the skirt.
+    pub(crate) fn vertices(&self) -> Vec<TerrainVertex> {
+        let subdivisions = self.lod_subdivisions();
+        let vertex = |x: u32, z: u32, drop: f32| {
+            let t = UVec2::new(x, z).as_vec2() / subdivisions.as_vec2();
+            TerrainVertex {
+                position: (t - 0.5) * self.size,
+                uv: self.uv_rect.min + t * self.uv_rect.size(),
+                drop,
+                _padding: 0.0,
+            }
+        };
+
+        let mut vertices: Vec<TerrainVertex> = (0..=subdivisions.y)
+            .flat_map(|z| (0..=subdivisions.x).map(move |x| (x, z)))
+            .map(|(x, z)| vertex(x, z, 0.0))
+            .collect();
+        if self.skirt_depth > 0.0 {
+            vertices.extend(self.border().into_iter().map(|(x, z)| vertex(x, z, self.skirt_depth)));
+        }
+        vertices
+    }
+
+    fn indices(&self) -> Vec<u32> {
+        let subdivisions = self.lod_subdivisions();
+        let row = subdivisions.x + 1;
+        let index = |x: u32, z: u32| z * row + x;
+
+        let mut indices: Vec<u32> = (0..subdivisions.y)
+            .flat_map(|z| (0..subdivisions.x).map(move |x| (x, z)))
+            .flat_map(|(x, z)| {
+                let (a, b, c, d) = (index(x, z), index(x + 1, z), index(x, z + 1), index(x + 1, z + 1));
+                [a, c, b, b, c, d]
+            })
+            .collect();
+
+        if self.skirt_depth > 0.0 {
+            let border = self.border();
+            let skirt_start = row * (subdivisions.y + 1);
+            for i in 0..border.len() {
+                let j = (i + 1) % border.len();
+                let (top_i, top_j) = (index(border[i].0, border[i].1), index(border[j].0, border[j].1));
+                let (bottom_i, bottom_j) = (skirt_start + i as u32, skirt_start + j as u32);
+                indices.extend([top_i, bottom_i, top_j, top_j, bottom_i, bottom_j]);
+            }
+        }
+        indices
+    }
+
+    /// Grid coordinates around the border, in the order that makes skirt quads face outwards.
+    fn border(&self) -> Vec<(u32, u32)> {
+        let UVec2 { x: width, y: depth } = self.lod_subdivisions();
+        (0..width).rev().map(|x| (x + 1, 0))
+            .chain((0..depth).map(|z| (0, z)))
+            .chain((0..width).map(|x| (x, depth)))
+            .chain((0..depth).rev().map(|z| (width, z + 1)))
+            .collect()
+    }
+}
+
+/// Heights from the red channel of a 2D noise image, sampled on the CPU.
+#[derive(Clone, Debug, PartialEq)]
+pub struct NoiseHeightmap {
+    pub width: u32,
+    pub height: u32,
+    /// Row by row, from 0 to 1.
+    pub heights: Vec<f32>,
+}
+
+impl NoiseHeightmap {
+    /// `data` is `Rgba8Unorm`, the format noise is generated in.
+    pub fn from_rgba8(width: u32, height: u32, data: &[u8]) -> Self {
+        Self {
+            width,
+            height,
+            heights: data.chunks_exact(4).take((width * height) as usize).map(|texel| texel[0] as f32 / 255.0).collect(),
+        }
+    }
+
+    /// Reads an `Rgba8Unorm` image's data on the CPU. Noise generated into an image only exists on the GPU,
+    /// a [`NoiseTerrain`] component reads it back instead.
+    pub fn from_image(image: &Image) -> Option<Self> {
+        if image.texture_descriptor.format != bevy::render::render_resource::TextureFormat::Rgba8Unorm { return None; }
+        let size = image.size();
+        Some(Self::from_rgba8(size.x, size.y, &image.data))
+    }
+
+    pub fn size(&self) -> UVec2 {
+        UVec2::new(self.width, self.height)
+    }
+
+    /// Bilinear sample, the edge texels' centres are at uv 0 and 1 so neighbouring chunks line up.
+    pub fn sample(&self, uv: Vec2) -> f32 {
+        let max = self.size().saturating_sub(UVec2::ONE);
+        let texel = uv.clamp(Vec2::ZERO, Vec2::ONE) * max.as_vec2();
+        let start = texel.floor().as_uvec2().min(max);
+        let end = (start + 1).min(max);
+        let t = texel - start.as_vec2();
+
+        let height = |x: u32, y: u32| self.heights.get((y * self.width + x) as usize).copied().unwrap_or(0.0);
+        let top = height(start.x, start.y).lerp(height(end.x, start.y), t.x);
+        let bottom = height(start.x, end.y).lerp(height(end.x, end.y), t.x);
+        top.lerp(bottom, t.y)
+    }
+
+    /// Change in height per texel along x and y.
+    pub fn gradient(&self, uv: Vec2) -> Vec2 {
+        let texel = 1.0 / self.size().saturating_sub(UVec2::ONE).max(UVec2::ONE).as_vec2();
+        // One texel either side, or up to the edge.
+        let difference = |axis: Vec2| {
+            let (low, high) = ((uv - axis * texel).max(Vec2::ZERO), (uv + axis * texel).min(Vec2::ONE));
+            let distance = ((high - low) / texel).dot(axis);
+            if distance > 0.0 { (self.sample(high) - self.sample(low)) / distance } else { 0.0 }
+        };
+        Vec2::new(difference(Vec2::X), difference(Vec2::Y))
+    }
+}
+
+/// A heightmap being read back for a [`NoiseTerrain`] built on the CPU.
+#[derive(Component, Default)]
+pub(crate) struct NoiseTerrainReadback(Arc<Mutex<Option<NoiseHeightmap>>>);
+
+pub(crate) fn build_noise_terrain(
+    mut commands: Commands,
+    terrains: Query<(Entity, &NoiseTerrain), Changed<NoiseTerrain>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut readback_queue: ResMut<ComputeNoiseReadbackQueue>,
+    mut displacements: ResMut<gpu::NoiseTerrainDisplacements>,
+) {
+    for (entity, terrain) in &terrains {
+        let mut entity = commands.entity(entity);
+        entity.insert(terrain.aabb());
+
+        if terrain.gpu_displacement {
+            let mesh = meshes.add(terrain.flat_mesh());
+            displacements.push(terrain, mesh.id());
+            entity.insert(Mesh3d(mesh)).remove::<NoiseTerrainReadback>();
+            continue;
+        }
+
+        let readback = NoiseTerrainReadback::default();
+        let heightmap = readback.0.clone();
+        readback_queue.read(terrain.heightmap.clone(), move |size, data| {
+            if let Some(data) = data {
+                *heightmap.lock().unwrap() = Some(NoiseHeightmap::from_rgba8(size.width(), size.height(), &data));
+            }
+        });
+        entity.insert(readback);
+    }
+}
+
+pub(crate) fn finish_noise_terrain_readbacks(
+    mut commands: Commands,
+    terrains: Query<(Entity, &NoiseTerrain, &NoiseTerrainReadback)>,
+    mut meshes: ResMut<Assets<Mesh>>,
+) {
+    for (entity, terrain, readback) in &terrains {
+        let Some(heightmap) = readback.0.lock().unwrap().take() else { continue };
+        commands.entity(entity)
+            .insert(Mesh3d(meshes.add(terrain.mesh(&heightmap))))
+            .remove::<NoiseTerrainReadback>();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn skirt_faces_outwards() {
+        let terrain = NoiseTerrain { size: Vec2::splat(2.0), subdivisions: UVec2::new(2, 3), skirt_depth: 1.0, ..default() };
+        let vertices = terrain.vertices();
+        let indices = terrain.indices();
+        assert_eq!(vertices.len(), 3 * 4 + 2 * (2 + 3));
+        assert_eq!(indices.len(), 6 * 2 * 3 + 6 * 2 * (2 + 3));
+
+        let position = |index: u32| {
+            let vertex = vertices[index as usize];
+            Vec3::new(vertex.position.x, -vertex.drop, vertex.position.y)
+        };
+        for triangle in indices.chunks(3) {
+            let [a, b, c] = [position(triangle[0]), position(triangle[1]), position(triangle[2])];
+            let normal = (b - a).cross(c - a);
+            let centre = (a + b + c) / 3.0;
+            match centre.y == 0.0 {
+                true => assert!(normal.y > 0.0),
+                false => assert!(normal.dot(centre.with_y(0.0)) > 0.0),
+            }
+        }
+    }
+
+    #[test]
+    fn heightmap_samples_corners() {
+        let heightmap = NoiseHeightmap { width: 2, height: 2, heights: vec![0.0, 1.0, 0.5, 0.5] };
+        assert_eq!(heightmap.sample(Vec2::ZERO), 0.0);
+        assert_eq!(heightmap.sample(Vec2::X), 1.0);
+        assert_eq!(heightmap.sample(Vec2::new(0.5, 0.0)), 0.5);
+        assert_eq!(heightmap.gradient(Vec2::ZERO), Vec2::new(1.0, 0.5));
+
+        let terrain = NoiseTerrain { subdivisions: UVec2::ONE, height_scale: 2.0, ..default() };
+        let mesh = terrain.mesh(&heightmap);
+        let Some(bevy::render::mesh::VertexAttributeValues::Float32x3(positions)) = mesh.attribute(Mesh::ATTRIBUTE_POSITION) else { panic!() };
+        assert_eq!(positions.iter().map(|position| position[1]).collect::<Vec<_>>(), vec![0.0, 2.0, 1.0, 1.0]);
+    }
+}
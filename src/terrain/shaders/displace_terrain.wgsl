@@ -0,0 +1,70 @@
+// Displaces a `NoiseTerrain` by its heightmap, writing vertices in the mesh's layout:
+// position, normal then uv, interleaved.
+
+struct Config {
+    size: vec2<f32>,
+    uv_rect_size: vec2<f32>,
+    height_scale: f32,
+    vertex_count: u32,
+    _padding0: u32,
+    _padding1: u32,
+}
+
+struct TerrainVertex {
+    position: vec2<f32>,
+    uv: vec2<f32>,
+    drop: f32,
+    _padding: f32,
+}
+
+@group(0) @binding(0) var heightmap: texture_2d<f32>;
+@group(0) @binding(1) var<uniform> config: Config;
+@group(0) @binding(2) var<storage, read> vertices: array<TerrainVertex>;
+@group(0) @binding(3) var<storage, read_write> output: array<f32>;
+
+// Bilinear sample with the edge texels' centres at uv 0 and 1, the same as `NoiseHeightmap::sample`.
+fn sample_height(uv: vec2<f32>) -> f32 {
+    let max_texel = vec2<u32>(textureDimensions(heightmap)) - 1u;
+    let texel = clamp(uv, vec2(0.0), vec2(1.0)) * vec2<f32>(max_texel);
+    let start = min(vec2<u32>(floor(texel)), max_texel);
+    let end = min(start + 1u, max_texel);
+    let t = texel - vec2<f32>(start);
+
+    let top = mix(textureLoad(heightmap, start, 0).r, textureLoad(heightmap, vec2(end.x, start.y), 0).r, t.x);
+    let bottom = mix(textureLoad(heightmap, vec2(start.x, end.y), 0).r, textureLoad(heightmap, end, 0).r, t.x);
+    return mix(top, bottom, t.y);
+}
+
+// Change in height per texel along `axis`, one texel either side or up to the edge.
+fn difference(uv: vec2<f32>, axis: vec2<f32>, texel: vec2<f32>) -> f32 {
+    let low = max(uv - axis * texel, vec2(0.0));
+    let high = min(uv + axis * texel, vec2(1.0));
+    let distance = dot((high - low) / texel, axis);
+    if distance <= 0.0 { return 0.0; }
+    return (sample_height(high) - sample_height(low)) / distance;
+}
+
+@compute @workgroup_size(64)
+fn main(@builtin(global_invocation_id) invocation_id: vec3<u32>) {
+    let index = invocation_id.x;
+    if index >= config.vertex_count { return; }
+    let vertex = vertices[index];
+
+    let texels = max(vec2<f32>(textureDimensions(heightmap)) - 1.0, vec2(1.0));
+    let texel = 1.0 / texels;
+    let world_per_texel = config.size / (config.uv_rect_size * texels);
+
+    let height = sample_height(vertex.uv) * config.height_scale - vertex.drop;
+    let gradient = vec2(difference(vertex.uv, vec2(1.0, 0.0), texel), difference(vertex.uv, vec2(0.0, 1.0), texel));
+    let slope = gradient * config.height_scale / world_per_texel;
+    let normal = normalize(vec3(-slope.x, 1.0, -slope.y));
+
+    let values = array<f32, 8>(
+        vertex.position.x, height, vertex.position.y,
+        normal.x, normal.y, normal.z,
+        vertex.uv.x, vertex.uv.y,
+    );
+    for (var i = 0u; i < 8u; i++) {
+        output[index * 8u + i] = values[i];
+    }
+}